@@ -4,116 +4,296 @@
 
 //! The IMPL interpreter.
 
-mod operand;
-mod operation;
+pub mod env;
+pub mod err;
+pub mod graphic;
+pub mod operand;
+pub mod operation;
 
-use crate::{parser::{self, Expr}, span::Span};
-use operand::{Operand, RawOperand};
-use operation::{OPERATIONS, Operation};
-use std::fmt;
+pub use env::Environment;
+pub use err::Error;
 
-pub fn eval_ast(ast: Expr) -> Result<Output, Span<Error>> {
-    eval_expr(ast).map(|it| Output::Text(it.to_string()))
+use crate::{parser::{self, Expr, Symbol}, span::Span};
+use env::Function;
+use err::{Cause, Frame};
+use operand::Operand;
+use operation::{Exe, Operation, Sig};
+use std::{ops::Range, rc::Rc};
+
+pub fn eval_ast(ast: Span<Expr>, env: &mut Environment) -> Result<Output, Span<Error>> {
+    let result = eval_expr(&ast.inner, ast.range, env)?;
+    // The result may be referred to by later expressions.
+    env.push_result(result.clone());
+
+    Ok(match result {
+        Operand::Graphic(it) => Output::Graphic(it),
+        it => Output::Text(it.to_string()),
+    })
 }
 
-fn eval_expr(expr: Expr) -> Result<Operand, Span<Error>> {
-    let operation = get_operation(expr.operation)?;
-    let operands = construct_raw_operands(&operation.inner, expr.operands)?;
+fn eval_expr(expr: &Expr, range: Range<usize>, env: &mut Environment) -> Result<Operand, Span<Error>> {
+    let name = expr.operation.inner.name.as_str();
+
+    if expr.operands.is_empty() {
+        // An expression consisting only of a bound name, such as `(x)`, evaluates to its value just
+        // as constants do.
+        if let Some(val) = env.lookup(name) {
+            return Ok(val.clone());
+        }
+    }
+
+    // User-defined functions shadow operations of the same name.
+    if let Some(function) = env.function(name) {
+        return call_function(Rc::clone(function), expr, range, env);
+    }
+
+    // Errors that originate in a subexpression are annotated with this expression, which was being
+    // evaluated at the time.
+    let in_frame = |e: Span<Error>| {
+        e.map(|e| e.with_frame(Span::new(Frame { expr: expr.to_string() }, range.clone())))
+    };
+
+    let operation = get_operation(&expr.operation, env)?;
+    let operands = eval_operands(expr.operands.as_slice(), Some(&operation.inner.sig), env)
+        .map_err(in_frame)?;
+    let operand_ranges: Vec<_> = operands.iter().map(|it| it.range.clone()).collect();
+    let mut operands = construct_operands(&operation.inner, operands, env)?;
     let _ = check_operand_count(&operation, operands.as_slice())?;
+    append_default_operands(&operation.inner, &mut operands);
+
+    execute_operation(&operation, operands.as_slice(), operand_ranges.as_slice(), env, in_frame)
+}
+
+/// Calls a user-defined function.
+fn call_function(
+    function: Rc<Function>,
+    expr: &Expr,
+    range: Range<usize>,
+    env: &mut Environment,
+) -> Result<Operand, Span<Error>> {
+    let operands = eval_operands(expr.operands.as_slice(), None, env)
+        .map_err(|e| {
+            e.map(|e| e.with_frame(Span::new(Frame { expr: expr.to_string() }, range.clone())))
+        })?
+        .into_iter()
+        .map(|operand| resolve_operand(operand, None, env))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(extra) = operands.get(function.params.len()) {
+        return Err(Span::new(Error::new(Cause::ExtraOperand), extra.range.clone()));
+    }
+    if operands.len() < function.params.len() {
+        return Err(Span::new(Error::new(Cause::MissingOperand), expr.operation.range.clone()));
+    }
+
+    let args = function
+        .params
+        .iter()
+        .cloned()
+        .zip(operands.into_iter().map(|operand| operand.inner));
+    if !env.enter_call(args) {
+        return Err(Span::new(
+            Error::new(Cause::CallDepthExceeded { max: env.max_call_depth() }),
+            expr.operation.range.clone(),
+        ));
+    }
+
+    let result = eval_quoted(&function.body, env);
+    env.exit_call();
+
+    result.map_err(|e| {
+        // The body of the function may have been defined in an earlier input, in which case its
+        // spans are meaningless to the user. The error is instead spanned to the call.
+        let frame = Span::new(Frame { expr: function.body.inner.to_string() }, range.clone());
+
+        Span::new(e.inner.without_backtrace().with_frame(frame), range)
+    })
+}
+
+fn get_operation(
+    operation: &Span<parser::Operation>,
+    env: &Environment,
+) -> Result<Span<Rc<Operation>>, Span<Error>> {
+    env.operation(operation.inner.name.as_str())
+        .map(|it| Span::new(Rc::clone(it), operation.range.clone()))
+        .ok_or_else(|| Span::new(
+            Error::new(Cause::UnknownOperation {
+                name: operation.inner.name.to_string(),
+            }),
+            operation.range.clone(),
+        ))
+}
+
+/// Evaluates an operand that was left unevaluated, resolving it if it is a symbol.
+fn eval_quoted(operand: &Span<parser::Operand>, env: &mut Environment) -> Result<Operand, Span<Error>> {
+    let operand = eval_operand(operand, false, env)?;
+
+    resolve_operand(operand, None, env).map(|it| it.inner)
+}
 
-    execute_operation(&operation, operands.as_slice())
+/// Evaluates the operands of an expression.
+///
+/// Operands are left unevaluated where the signature, if any, expects an expression.
+fn eval_operands(
+    operands: &[Span<parser::Operand>],
+    sig: Option<&Sig>,
+    env: &mut Environment,
+) -> Result<Vec<Span<Operand>>, Span<Error>> {
+    operands
+        .iter()
+        .enumerate()
+        .map(|(idx, operand)| {
+            let expected_kind = sig.and_then(|sig| sig.kind_at(idx));
+
+            eval_operand(operand, expected_kind == Some(operand::Kind::Expr), env)
+        })
+        .collect()
 }
 
-fn get_operation(operation: Span<parser::Operation>) -> Result<Span<&'static Operation>, Span<Error>> {
-    OPERATIONS
-        .get(operation.inner.name.as_str())
-        .map(|it| operation.clone().map(|_| it))
-        .ok_or_else(|| operation.map(|operation| Error::UnknownOperation {
-            name: operation.name.to_string(),
-        }))
+fn eval_operand(
+    operand: &Span<parser::Operand>,
+    should_quote: bool,
+    env: &mut Environment,
+) -> Result<Span<Operand>, Span<Error>> {
+    let inner = match &operand.inner {
+        _ if should_quote => Operand::Expr(operand.clone()),
+        // Recursively evaluate subexpressions.
+        parser::Operand::Expr(it) => eval_expr(it, operand.range.clone(), env)?,
+        // The elements of lists are evaluated eagerly.
+        parser::Operand::List(it) => Operand::List(
+            it.elems
+                .iter()
+                .map(|elem| eval_quoted(elem, env))
+                .collect::<Result<_, _>>()?,
+        ),
+        parser::Operand::Rational(it) => Operand::Rational(it.clone()),
+        parser::Operand::StrLit(it) => Operand::StrLit(it.clone()),
+        parser::Operand::Symbol(it) => Operand::Symbol(it.clone()),
+    };
+
+    Ok(Span::new(inner, operand.range.clone()))
 }
 
-fn construct_raw_operands(
+fn construct_operands(
     operation: &Operation,
-    operands: Vec<Span<parser::Operand>>,
-) -> Result<Vec<RawOperand>, Span<Error>> {
+    operands: Vec<Span<Operand>>,
+    env: &mut Environment,
+) -> Result<Vec<Operand>, Span<Error>> {
     operands
         .into_iter()
-        // Recursively evaluate subexpressions (see [`Operand::from`]).
-        .map(|operand| operand.map(|inner| Operand::from(inner)))
         .enumerate()
         .map(|(idx, operand)| {
-            if idx >= operation.sig.len() {
-                return Err(operand.map(|_| Error::ExtraOperand));
-            }
+            let Some(kind) = operation.sig.kind_at(idx) else {
+                return Err(operand.map(|_| Error::new(Cause::ExtraOperand)));
+            };
 
-            let _ = type_check_operand(&operand, operation.sig[idx])?;
+            let operand = resolve_operand(operand, Some(kind), env)?;
+            let _ = type_check_operand(&operand, kind)?;
 
-            Ok(operand.inner.raw())
+            Ok(operand.inner)
         })
         .collect()
 }
 
+/// Replaces a symbol with the value it refers to, unless a symbol is expected.
+fn resolve_operand(
+    operand: Span<Operand>,
+    expected_kind: Option<operand::Kind>,
+    env: &mut Environment,
+) -> Result<Span<Operand>, Span<Error>> {
+    match operand.inner {
+        Operand::Symbol(symbol) if expected_kind != Some(operand::Kind::Symbol) => {
+            resolve_symbol(Span::new(symbol, operand.range), env)
+        }
+        _ => Ok(operand),
+    }
+}
+
+/// Looks up the value of a symbol.
+///
+/// Symbols refer to bindings in the environment or, failing that, to constants---operations that
+/// take no operands---of the same name. Bindings therefore shadow constants.
+fn resolve_symbol(symbol: Span<Symbol>, env: &mut Environment) -> Result<Span<Operand>, Span<Error>> {
+    if let Some(val) = env.lookup(symbol.inner.name.as_str()) {
+        return Ok(Span::new(val.clone(), symbol.range));
+    }
+
+    let constant = env
+        .operation(symbol.inner.name.as_str())
+        .filter(|operation| operation.sig.min_len() == 0 && operation.sig.kind_at(0).is_none())
+        .map(Rc::clone);
+    let Some(constant) = constant else {
+        return Err(symbol.map(|symbol| Error::new(Cause::UnboundSymbol { name: symbol.name })));
+    };
+
+    let range = symbol.range;
+    execute_operation(&Span::new(constant, range.clone()), &[], &[], env, std::convert::identity)
+        .map(|val| Span::new(val, range))
+}
+
 fn type_check_operand(operand: &Span<Operand>, operand_kind: operand::Kind) -> Result<(), Span<Error>> {
     if kind_is_valid(&operand.inner, operand_kind) {
         Ok(())
     } else {
-        Err(Span::new(Error::UnexpectedOperandKind, operand.range.clone()))
+        Err(Span::new(Error::new(Cause::UnexpectedOperandKind), operand.range.clone()))
     }
 }
 
 fn kind_is_valid(actual_operand: &Operand, expected_kind: operand::Kind) -> bool {
-    actual_operand.kind() == expected_kind
+    match expected_kind {
+        operand::Kind::Any => true,
+        operand::Kind::Number => matches!(actual_operand, Operand::Rational(_) | Operand::Complex(_)),
+        _ => actual_operand.kind() == expected_kind,
+    }
 }
 
-fn check_operand_count(operation: &Span<&Operation>, operands: &[RawOperand]) -> Result<(), Span<Error>> {
-    if operands.len() < operation.inner.sig.len() {
-        Err(Span::new(Error::MissingOperand, operation.range.clone()))
+fn check_operand_count(operation: &Span<Rc<Operation>>, operands: &[Operand]) -> Result<(), Span<Error>> {
+    if operands.len() < operation.inner.sig.min_len() {
+        Err(Span::new(Error::new(Cause::MissingOperand), operation.range.clone()))
     } else {
         Ok(())
     }
 }
 
+/// Substitutes defaults for omitted optional operands.
+fn append_default_operands(operation: &Operation, operands: &mut Vec<Operand>) {
+    let omitted = operation.sig.params.iter().skip(operands.len());
+    operands.extend(omitted.filter_map(|param| param.default).map(|default| default()));
+}
+
 /// Execute the operation with its operands.
-fn execute_operation(operation: &Span<&Operation>, operands: &[RawOperand]) -> Result<Operand, Span<Error>> {
-    (operation.inner.exe)(operands)
-        .map_err(|e| Span::new(Error::Operation(e), operation.range.clone()))
+///
+/// Errors are spanned to the operand responsible for them or, if there is no such operand or it was
+/// omitted, to the operation. Errors from expressions evaluated by the operation itself are passed
+/// through `in_frame`.
+fn execute_operation(
+    operation: &Span<Rc<Operation>>,
+    operands: &[Operand],
+    operand_ranges: &[Range<usize>],
+    env: &mut Environment,
+    in_frame: impl FnOnce(Span<Error>) -> Span<Error>,
+) -> Result<Operand, Span<Error>> {
+    let result = match &operation.inner.exe {
+        Exe::Builtin(exe) => exe(env, operands),
+        Exe::Custom(exe) => exe(operands),
+    };
+
+    result.map_err(|e| {
+        if let operation::Cause::Eval(e) = e.cause() {
+            return in_frame(e.as_ref().clone());
+        }
+
+        let range = e
+            .operand_idx()
+            .and_then(|idx| operand_ranges.get(idx))
+            .unwrap_or(&operation.range)
+            .clone();
+
+        Span::new(Error::new(Cause::Operation(e)), range)
+    })
 }
 
 pub enum Output {
     Text(String),
-    Graphic,
-}
-
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Error {
-    ExtraOperand,
-    MissingOperand,
-    Operation(operation::Error),
-    UnexpectedOperandKind,
-    UnknownOperation { name: String },
-}
-
-impl std::error::Error for Error {}
-
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::ExtraOperand => {
-                write!(f, "extra operand")
-            }
-            Self::MissingOperand => {
-                write!(f, "missing operand")
-            }
-            Self::Operation(e) => {
-                e.fmt(f)
-            }
-            Self::UnexpectedOperandKind => {
-                write!(f, "unexpected operand kind")
-            }
-            Self::UnknownOperation { name } => {
-                write!(f, "unknown operation \"{}\"", name)
-            }
-        }
-    }
+    Graphic(Rc<graphic::Graphic>),
 }
@@ -0,0 +1,250 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Variable bindings and user-defined functions.
+
+use super::{operand::Operand, operation::{self, Operation, Sig}};
+use crate::{parser, span::Span};
+use ahash::RandomState;
+use std::{collections::HashMap, rc::Rc};
+
+/// The default maximum depth of nested function calls.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 256;
+
+/// The largest maximum depth of nested function calls that may be set.
+///
+/// Function calls are evaluated recursively, so deeper recursion could overflow the stack of the
+/// evaluating thread, which aborts the process rather than failing with an error. This limit leaves
+/// room for function bodies of moderate nesting on a stack of 8 MiB, the usual size for the main
+/// thread.
+pub const MAX_CALL_DEPTH: usize = 512;
+
+/// The named values, functions, and operations visible to an evaluation.
+///
+/// Bindings are organized into a stack of scopes, the bottommost of which is the global scope.
+/// Global bindings persist for the lifetime of the environment, so one environment may be shared by
+/// every line entered into a shell or every expression in a script.
+///
+/// Bindings in inner scopes shadow bindings of the same name in outer scopes, and rebinding a name
+/// within the same scope replaces its previous value. Scopes are lexical: the body of a function
+/// sees its parameters and the global scope, but not the scopes of its caller.
+pub struct Environment {
+    scopes: Vec<Scope>,
+    functions: HashMap<String, Rc<Function>, RandomState>,
+    /// The operations that may be applied, which are initially the built-in ones.
+    operations: HashMap<String, Rc<Operation>, RandomState>,
+    angle_mode: AngleMode,
+    /// The values of all top-level expressions evaluated so far, in order.
+    results: Vec<Operand>,
+    /// The number of function calls currently being evaluated.
+    call_depth: usize,
+    max_call_depth: usize,
+}
+
+#[derive(Default)]
+struct Scope {
+    bindings: HashMap<String, Operand, RandomState>,
+    /// Whether this scope holds the parameters of a function call, in which case lookups do not
+    /// proceed past it to the scopes of the caller.
+    is_call: bool,
+}
+
+/// The unit in which trigonometric operations measure angles.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AngleMode {
+    /// 2π radians make a full turn.
+    #[default]
+    Radians,
+    /// 360 degrees make a full turn.
+    Degrees,
+    /// 400 gradians make a full turn.
+    Gradians,
+}
+
+impl AngleMode {
+    /// The size of a full turn in this unit, or `None` if it is irrational.
+    pub fn turn(self) -> Option<i64> {
+        match self {
+            Self::Radians => None,
+            Self::Degrees => Some(360),
+            Self::Gradians => Some(400),
+        }
+    }
+}
+
+/// A function defined in IMPL.
+pub(crate) struct Function {
+    pub params: Vec<String>,
+    pub body: Span<parser::Operand>,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            scopes: vec![Scope::default()],
+            functions: HashMap::default(),
+            operations: operation::BUILTINS
+                .iter()
+                .map(|(name, operation)| (name.to_string(), Rc::new(operation.clone())))
+                .collect(),
+            angle_mode: AngleMode::default(),
+            results: Vec::new(),
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+}
+
+impl Environment {
+    /// Sets the maximum depth of nested function calls, beyond which evaluation fails.
+    ///
+    /// Values greater than [`MAX_CALL_DEPTH`] are clamped to it.
+    pub fn set_max_call_depth(&mut self, it: usize) {
+        self.max_call_depth = it.min(MAX_CALL_DEPTH);
+    }
+
+    pub fn angle_mode(&self) -> AngleMode {
+        self.angle_mode
+    }
+
+    /// Sets the unit in which trigonometric operations measure angles.
+    pub fn set_angle_mode(&mut self, it: AngleMode) {
+        self.angle_mode = it;
+    }
+
+    /// The values of all top-level expressions evaluated so far, in order, which `$n` refers to by
+    /// one-based index.
+    pub fn results(&self) -> &[Operand] {
+        self.results.as_slice()
+    }
+
+    /// Records the value of a top-level expression.
+    pub(crate) fn push_result(&mut self, val: Operand) {
+        self.results.push(val);
+    }
+
+    /// The names of all global bindings.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scopes[0].bindings.keys().map(String::as_str)
+    }
+
+    /// The names of all user-defined functions.
+    pub fn function_names(&self) -> impl Iterator<Item = &str> {
+        self.functions.keys().map(String::as_str)
+    }
+
+    /// The names of all operations.
+    pub fn operation_names(&self) -> impl Iterator<Item = &str> {
+        self.operations.keys().map(String::as_str)
+    }
+
+    /// The signature of the operation registered under the given name, if any.
+    pub fn operation_sig(&self, name: &str) -> Option<&Sig> {
+        self.operations.get(name).map(|it| it.sig())
+    }
+
+    /// The parameter names of the user-defined function of the given name, if any.
+    pub fn function_params(&self, name: &str) -> Option<&[String]> {
+        self.functions.get(name).map(|it| it.params.as_slice())
+    }
+
+    /// Makes an operation available under the given name, replacing and returning any operation
+    /// previously registered under that name.
+    pub fn register_operation(&mut self, name: impl Into<String>, operation: Operation) -> Option<Operation> {
+        self.operations
+            .insert(name.into(), Rc::new(operation))
+            .map(|it| Rc::try_unwrap(it).unwrap_or_else(|it| (*it).clone()))
+    }
+
+    /// Removes and returns the operation registered under the given name, if any.
+    pub fn unregister_operation(&mut self, name: &str) -> Option<Operation> {
+        self.operations
+            .remove(name)
+            .map(|it| Rc::try_unwrap(it).unwrap_or_else(|it| (*it).clone()))
+    }
+
+    /// Binds a value to a name in the innermost scope.
+    pub(crate) fn bind(&mut self, name: String, val: Operand) {
+        self.innermost_mut().bindings.insert(name, val);
+    }
+
+    /// Looks up the value bound to a name, starting from the innermost scope.
+    pub(crate) fn lookup(&self, name: &str) -> Option<&Operand> {
+        let (global, local) = self
+            .scopes
+            .split_first()
+            // This `expect` is OK because the global scope is never popped.
+            .expect("environment should have a global scope");
+
+        let mut visible = Vec::new();
+        for scope in local.iter().rev() {
+            visible.push(scope);
+            if scope.is_call {
+                break;
+            }
+        }
+        visible.push(global);
+
+        visible.into_iter().find_map(|scope| scope.bindings.get(name))
+    }
+
+    /// Enters a new innermost scope.
+    pub(crate) fn push_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    /// Exits the innermost scope, discarding its bindings.
+    pub(crate) fn pop_scope(&mut self) {
+        // The global scope is never popped.
+        if self.scopes.len() > 1 {
+            let _ = self.scopes.pop();
+        }
+    }
+
+    pub(crate) fn define(&mut self, name: String, function: Function) {
+        self.functions.insert(name, Rc::new(function));
+    }
+
+    pub(crate) fn function(&self, name: &str) -> Option<&Rc<Function>> {
+        self.functions.get(name)
+    }
+
+    pub(crate) fn operation(&self, name: &str) -> Option<&Rc<Operation>> {
+        self.operations.get(name)
+    }
+
+    /// Enters a function call, binding its arguments in a new scope.
+    ///
+    /// Returns `false`, without entering the call, if the maximum call depth has been reached.
+    pub(crate) fn enter_call(&mut self, args: impl IntoIterator<Item = (String, Operand)>) -> bool {
+        if self.call_depth >= self.max_call_depth {
+            return false;
+        }
+
+        self.call_depth += 1;
+        self.scopes.push(Scope {
+            bindings: args.into_iter().collect(),
+            is_call: true,
+        });
+
+        true
+    }
+
+    /// Exits the innermost function call.
+    pub(crate) fn exit_call(&mut self) {
+        self.call_depth -= 1;
+        self.pop_scope();
+    }
+
+    pub(crate) fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    fn innermost_mut(&mut self) -> &mut Scope {
+        self.scopes
+            .last_mut()
+            // This `expect` is OK because the global scope is never popped.
+            .expect("environment should have a global scope")
+    }
+}
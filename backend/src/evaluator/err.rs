@@ -0,0 +1,109 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::operation;
+use crate::span::Span;
+use std::fmt;
+
+/// An evaluator error.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Error {
+    /// The cause of the error.
+    cause: Cause,
+    /// The expressions that were being evaluated when the error occurred, from innermost to
+    /// outermost.
+    backtrace: Vec<Span<Frame>>,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.cause.fmt(f)
+    }
+}
+
+impl Error {
+    pub fn new(cause: Cause) -> Self {
+        Self {
+            cause,
+            backtrace: Vec::new(),
+        }
+    }
+
+    /// Discards the backtrace.
+    pub fn without_backtrace(mut self) -> Self {
+        self.backtrace.clear();
+
+        self
+    }
+
+    /// Appends a frame for an enclosing expression to the backtrace.
+    pub fn with_frame(mut self, frame: Span<Frame>) -> Self {
+        self.backtrace.push(frame);
+
+        self
+    }
+
+    pub fn cause(&self) -> &Cause {
+        &self.cause
+    }
+
+    pub fn backtrace(&self) -> &[Span<Frame>] {
+        self.backtrace.as_slice()
+    }
+}
+
+/// The cause of an [error](Error).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Cause {
+    CallDepthExceeded { max: usize },
+    ExtraOperand,
+    MissingOperand,
+    Operation(operation::Error),
+    UnboundSymbol { name: String },
+    UnexpectedOperandKind,
+    UnknownOperation { name: String },
+}
+
+impl fmt::Display for Cause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CallDepthExceeded { max } => {
+                write!(f, "maximum call depth of {} exceeded", max)
+            }
+            Self::ExtraOperand => {
+                write!(f, "extra operand")
+            }
+            Self::MissingOperand => {
+                write!(f, "missing operand")
+            }
+            Self::Operation(e) => {
+                e.fmt(f)
+            }
+            Self::UnboundSymbol { name } => {
+                write!(f, "unbound symbol \"{}\"", name)
+            }
+            Self::UnexpectedOperandKind => {
+                write!(f, "unexpected operand kind")
+            }
+            Self::UnknownOperation { name } => {
+                write!(f, "unknown operation \"{}\"", name)
+            }
+        }
+    }
+}
+
+/// An entry in the backtrace of an [error](Error).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    /// The source of the expression being evaluated, as rendered by the parser.
+    pub expr: String,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "while evaluating {}", self.expr)
+    }
+}
@@ -0,0 +1,91 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Descriptions of graphical output.
+//!
+//! The backend only describes graphics; rendering them to files or the terminal is left to the
+//! frontend.
+
+/// A graph of one or more functions of a variable.
+#[derive(Clone, Debug)]
+pub struct Graphic {
+    /// The name of the variable along the horizontal axis.
+    pub var: String,
+    /// The range of the horizontal axis.
+    pub x_range: (f64, f64),
+    /// The range of the vertical axis, which is chosen to fit the series.
+    pub y_range: (f64, f64),
+    pub series: Vec<Series>,
+}
+
+/// The samples of one function.
+#[derive(Clone, Debug)]
+pub struct Series {
+    /// The plotted expression.
+    pub label: String,
+    /// The sampled points, in ascending order of `x`. `y` is `None` where the function is
+    /// undefined.
+    pub points: Vec<(f64, Option<f64>)>,
+}
+
+impl Graphic {
+    /// Creates a graphic from series, fitting the vertical axis to their values.
+    pub fn new(var: String, x_range: (f64, f64), series: Vec<Series>) -> Self {
+        let y_range = fit_range(series.iter().flat_map(|it| it.points.iter().filter_map(|(_, y)| *y)));
+
+        Self { var, x_range, y_range, series }
+    }
+
+    /// Splits a series into polylines, breaking it where the function is undefined or jumps by more
+    /// than the height of the graph, as at the poles of `tan`.
+    pub fn polylines(&self, series: &Series) -> Vec<Vec<(f64, f64)>> {
+        let height = self.y_range.1 - self.y_range.0;
+        let mut lines = Vec::new();
+        let mut line: Vec<(f64, f64)> = Vec::new();
+
+        for (x, y) in series.points.iter().copied() {
+            let Some(y) = y else {
+                lines.push(std::mem::take(&mut line));
+                continue;
+            };
+
+            if line.last().is_some_and(|(_, prev)| (y - prev).abs() > height) {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push((x, y));
+        }
+        lines.push(line);
+
+        lines.retain(|it| !it.is_empty());
+        lines
+    }
+}
+
+/// Chooses a range that fits values with a small margin.
+///
+/// Outliers---which are common near poles---are excluded so that they do not flatten the rest of
+/// the graph.
+fn fit_range(vals: impl Iterator<Item = f64>) -> (f64, f64) {
+    let mut vals: Vec<_> = vals.collect();
+    if vals.is_empty() {
+        return (-1.0, 1.0);
+    }
+    vals.sort_by(f64::total_cmp);
+
+    let percentile = |p: f64| vals[((vals.len() - 1) as f64 * p).round() as usize];
+    let (mut lo, mut hi) = (vals[0], vals[vals.len() - 1]);
+    let (inner_lo, inner_hi) = (percentile(0.05), percentile(0.95));
+    if (hi - lo) > 20.0 * (inner_hi - inner_lo) && (inner_hi > inner_lo) {
+        let spread = inner_hi - inner_lo;
+        lo = lo.max(inner_lo - spread);
+        hi = hi.min(inner_hi + spread);
+    }
+
+    if hi - lo <= f64::EPSILON * lo.abs().max(1.0) {
+        return (lo - 1.0, hi + 1.0);
+    }
+
+    let margin = 0.05 * (hi - lo);
+    (lo - margin, hi + margin)
+}
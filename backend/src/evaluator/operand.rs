@@ -2,32 +2,48 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use crate::parser::{self, Rational, StrLit, Symbol};
-use std::{fmt, mem::ManuallyDrop};
+//! The values that operations consume and produce.
 
+use super::graphic::Graphic;
+use crate::{
+    num::{complex::Complex, Number},
+    parser::{self, Rational, StrLit, Symbol},
+    span::Span,
+};
+use std::{fmt, rc::Rc};
+
+#[derive(Clone, Debug)]
 pub enum Operand {
+    /// An unevaluated operand, usually an expression.
+    Expr(Span<parser::Operand>),
+    /// A list of evaluated operands.
+    List(Rc<[Operand]>),
     Rational(Rational),
+    /// A complex number with a nonzero imaginary part.
+    ///
+    /// Complex results with an imaginary part of zero are [rationals](Self::Rational) instead.
+    Complex(Complex),
     StrLit(StrLit),
     Symbol(Symbol),
-}
-
-impl From<parser::Operand> for Operand {
-    fn from(it: parser::Operand) -> Self {
-        match it {
-            parser::Operand::Expr(it) => super::eval_expr(it).unwrap(),
-            parser::Operand::Rational(it) => Self::Rational(it),
-            parser::Operand::StrLit(it) => Self::StrLit(it),
-            parser::Operand::Symbol(it) => Self::Symbol(it),
-        }
-    }
+    Graphic(Rc<Graphic>),
 }
 
 impl fmt::Display for Operand {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            Self::Expr(it) => it.inner.fmt(f),
+            Self::List(it) => {
+                let elems: Vec<_> = it.iter().map(Operand::to_string).collect();
+                write!(f, "{{{}}}", elems.join(" "))
+            }
             Self::Rational(it) => it.fmt(f),
+            Self::Complex(it) => it.fmt(f),
             Self::StrLit(it) => it.fmt(f),
             Self::Symbol(it) => it.fmt(f),
+            Self::Graphic(it) => {
+                let labels: Vec<_> = it.series.iter().map(|it| it.label.as_str()).collect();
+                write!(f, "graph of {}", labels.join(", "))
+            }
         }
     }
 }
@@ -35,38 +51,152 @@ impl fmt::Display for Operand {
 impl Operand {
     pub fn kind(&self) -> Kind {
         match self {
+            Self::Expr(_) => Kind::Expr,
+            Self::List(_) => Kind::List,
             Self::Rational(_) => Kind::Rational,
+            Self::Complex(_) => Kind::Complex,
             Self::StrLit(_) => Kind::StrLit,
             Self::Symbol(_) => Kind::Symbol,
+            Self::Graphic(_) => Kind::Graphic,
         }
     }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Kind {
+    /// Any evaluated operand.
+    ///
+    /// This is only expected of operands, never the kind of an operand itself.
+    Any,
+    /// A rational or complex number.
+    ///
+    /// Like [`Any`](Self::Any), this is only expected of operands.
+    Number,
+    Expr,
+    List,
     Rational,
+    Complex,
     StrLit,
     Symbol,
+    Graphic,
 }
 
-impl Operand {
-    pub fn raw(self) -> RawOperand {
-        match self {
-            Self::Rational(it) => RawOperand {
-                rational: ManuallyDrop::new(it),
-            },
-            Self::StrLit(it) => RawOperand {
-                str_lit: ManuallyDrop::new(it),
-            },
-            Self::Symbol(it) => RawOperand {
-                symbol: ManuallyDrop::new(it),
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Any => "any",
+                Self::Number => "number",
+                Self::Expr => "expr",
+                Self::List => "list",
+                Self::Rational => "rational",
+                Self::Complex => "complex",
+                Self::StrLit => "string",
+                Self::Symbol => "symbol",
+                Self::Graphic => "graphic",
             },
+        )
+    }
+}
+
+/// A Rust type that can be extracted from an [`Operand`] of a particular kind.
+///
+/// This is implemented for the types that typed Rust closures passed to
+/// [`Operation::from_fn`](super::operation::Operation::from_fn) may accept.
+pub trait FromOperand: Sized {
+    /// The kind of operand this type is extracted from.
+    const KIND: Kind;
+
+    /// Extracts a value from an operand, or returns `None` if the operand is not of kind
+    /// [`KIND`](Self::KIND).
+    fn from_operand(operand: &Operand) -> Option<Self>;
+}
+
+/// A Rust type that can be converted into an [`Operand`].
+///
+/// This is implemented for the types that typed Rust closures passed to
+/// [`Operation::from_fn`](super::operation::Operation::from_fn) may return.
+pub trait IntoOperand {
+    fn into_operand(self) -> Operand;
+}
+
+macro_rules! impl_operand_conv {
+    ($ty:ty, $kind:ident) => {
+        impl FromOperand for $ty {
+            const KIND: Kind = Kind::$kind;
+
+            fn from_operand(operand: &Operand) -> Option<Self> {
+                match operand {
+                    Operand::$kind(it) => Some(it.clone()),
+                    _ => None,
+                }
+            }
+        }
+
+        impl IntoOperand for $ty {
+            fn into_operand(self) -> Operand {
+                Operand::$kind(self)
+            }
         }
+    };
+}
+
+impl_operand_conv!(Span<parser::Operand>, Expr);
+impl_operand_conv!(Rc<[Operand]>, List);
+impl_operand_conv!(Rational, Rational);
+impl_operand_conv!(StrLit, StrLit);
+impl_operand_conv!(Symbol, Symbol);
+impl_operand_conv!(Rc<Graphic>, Graphic);
+
+impl FromOperand for Number {
+    const KIND: Kind = Kind::Rational;
+
+    fn from_operand(operand: &Operand) -> Option<Self> {
+        Rational::from_operand(operand).map(|it| it.val)
     }
 }
 
-pub union RawOperand {
-    pub rational: ManuallyDrop<Rational>,
-    pub str_lit: ManuallyDrop<StrLit>,
-    pub symbol: ManuallyDrop<Symbol>,
+impl IntoOperand for Number {
+    fn into_operand(self) -> Operand {
+        Operand::Rational(Rational { val: self })
+    }
+}
+
+impl FromOperand for Complex {
+    const KIND: Kind = Kind::Number;
+
+    fn from_operand(operand: &Operand) -> Option<Self> {
+        match operand {
+            Operand::Rational(it) => Some(Complex::from_real(it.val.clone())),
+            Operand::Complex(it) => Some(it.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl IntoOperand for Complex {
+    /// Converts this number to a rational if it is real.
+    fn into_operand(self) -> Operand {
+        if self.is_real() {
+            Operand::Rational(Rational { val: self.re })
+        } else {
+            Operand::Complex(self)
+        }
+    }
+}
+
+impl FromOperand for Operand {
+    const KIND: Kind = Kind::Any;
+
+    fn from_operand(operand: &Operand) -> Option<Self> {
+        Some(operand.clone())
+    }
+}
+
+impl IntoOperand for Operand {
+    fn into_operand(self) -> Operand {
+        self
+    }
 }
@@ -2,84 +2,775 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use super::operand::{self, Operand};
-use ahash::RandomState;
-use std::{collections::HashMap, fmt};
+//! Operations and their signatures.
+//!
+//! The built-in operations are defined here. Embedders may define their own with
+//! [`Operation::new`] or [`Operation::from_fn`] and register them with an
+//! [`Interpreter`](crate::Interpreter).
 
+use super::{operand::{self, FromOperand, IntoOperand, Operand}, Environment};
+use crate::{
+    num::{complex::Complex, Number},
+    parser::{self, Rational},
+    span::Span,
+};
+use std::{borrow::Cow, fmt, rc::Rc};
+
+/// An operation that may be applied to operands in an IMPL expression.
+#[derive(Clone)]
 pub struct Operation {
-    pub sig: &'static [operand::Kind],
-    pub exe: fn(&[operand::RawOperand]) -> Result<Operand, Error>,
+    pub(crate) sig: Sig,
+    pub(crate) exe: Exe,
+}
+
+/// The implementation of an [`Operation`].
+#[derive(Clone)]
+pub(crate) enum Exe {
+    /// A built-in operation, which may access the environment.
+    Builtin(fn(&mut Environment, &[Operand]) -> Result<Operand, Error>),
+    /// An operation defined by an embedder.
+    Custom(Rc<CustomExe>),
+}
+
+pub(crate) type CustomExe = dyn Fn(&[Operand]) -> Result<Operand, Error>;
+
+impl Operation {
+    /// Creates an operation from a signature and a closure.
+    ///
+    /// The closure receives one operand for each operand in the expression followed by the defaults
+    /// of any omitted parameters, all of which are of the kinds declared by `sig`.
+    pub fn new(sig: Sig, exe: impl Fn(&[Operand]) -> Result<Operand, Error> + 'static) -> Self {
+        Self {
+            sig,
+            exe: Exe::Custom(Rc::new(exe)),
+        }
+    }
+
+    /// Creates an operation from a typed closure, whose signature is derived from the types of its
+    /// parameters.
+    ///
+    /// ```
+    /// use imp_backend::{evaluator::operation::Operation, num::Number};
+    ///
+    /// let hypot = Operation::from_fn(|a: Number, b: Number| {
+    ///     Ok(Number::approx(a.to_f64().hypot(b.to_f64())).unwrap_or(a))
+    /// });
+    /// assert_eq!(hypot.sig().min_len(), 2);
+    /// ```
+    pub fn from_fn<Args>(f: impl OperationFn<Args>) -> Self {
+        f.into_operation()
+    }
+
+    pub fn sig(&self) -> &Sig {
+        &self.sig
+    }
+}
+
+/// A typed Rust closure that may be converted into an [`Operation`].
+///
+/// This is implemented for closures of up to six parameters, each of which implements
+/// [`FromOperand`], that return a `Result` of a type that implements [`IntoOperand`].
+pub trait OperationFn<Args>: 'static {
+    fn into_operation(self) -> Operation;
+}
+
+macro_rules! impl_operation_fn {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg),*> OperationFn<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> Result<R, Error> + 'static,
+            R: IntoOperand,
+            $($arg: FromOperand,)*
+        {
+            #[allow(non_snake_case)]
+            fn into_operation(self) -> Operation {
+                let sig = Sig::new(vec![$(Param::new($arg::KIND)),*]);
+
+                Operation::new(sig, move |#[allow(unused_variables)] operands| {
+                    #[allow(unused_mut, unused_variables)]
+                    let mut operands = operands.iter();
+                    $(
+                        let $arg = operands
+                            .next()
+                            .and_then($arg::from_operand)
+                            // This `expect` is OK because operands are counted and type-checked
+                            // against the signature before the operation is executed.
+                            .expect("operand should match signature");
+                    )*
+
+                    self($($arg),*).map(IntoOperand::into_operand)
+                })
+            }
+        }
+    };
+}
+
+impl_operation_fn!();
+impl_operation_fn!(A);
+impl_operation_fn!(A, B);
+impl_operation_fn!(A, B, C);
+impl_operation_fn!(A, B, C, D);
+impl_operation_fn!(A, B, C, D, E);
+impl_operation_fn!(A, B, C, D, E, G);
+
+/// The signature of an [`Operation`].
+///
+/// Operands are matched against parameters in order. Parameters with defaults may only be followed
+/// by other parameters with defaults, and the variadic kind, if any, describes all operands that
+/// follow the parameters.
+#[derive(Clone)]
+pub struct Sig {
+    pub params: Cow<'static, [Param]>,
+    pub variadic: Option<operand::Kind>,
+}
+
+impl Sig {
+    pub fn new(params: Vec<Param>) -> Self {
+        Self {
+            params: Cow::Owned(params),
+            variadic: None,
+        }
+    }
+
+    /// Accepts any number of additional operands of the given kind after the parameters.
+    pub fn with_variadic(mut self, kind: operand::Kind) -> Self {
+        self.variadic = Some(kind);
+
+        self
+    }
+
+    /// The expected kind of the operand at the given index, or `None` if no operand is accepted at
+    /// that index.
+    pub fn kind_at(&self, idx: usize) -> Option<operand::Kind> {
+        self.params
+            .get(idx)
+            .map(|param| param.kind)
+            .or(self.variadic)
+    }
+
+    /// The minimum number of operands.
+    pub fn min_len(&self) -> usize {
+        self.params
+            .iter()
+            .take_while(|param| param.default.is_none())
+            .count()
+    }
+}
+
+/// Formats the kinds of parameters in order, with optional parameters in brackets and the
+/// variadic kind, if any, followed by an ellipsis; for example, `symbol expr [rational] expr...`.
+impl fmt::Display for Sig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<_> = self
+            .params
+            .iter()
+            .map(|param| match param.default {
+                Some(_) => format!("[{}]", param.kind),
+                None => param.kind.to_string(),
+            })
+            .collect();
+        if let Some(kind) = self.variadic {
+            parts.push(format!("{}...", kind));
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// A parameter of an [`Operation`].
+#[derive(Clone)]
+pub struct Param {
+    pub kind: operand::Kind,
+    /// Produces the operand used in place of an omitted one.
+    pub default: Option<fn() -> Operand>,
+}
+
+impl Param {
+    /// Creates a required parameter.
+    pub fn new(kind: operand::Kind) -> Self {
+        Self { kind, default: None }
+    }
+
+    /// Creates an optional parameter.
+    pub fn optional(kind: operand::Kind, default: fn() -> Operand) -> Self {
+        Self {
+            kind,
+            default: Some(default),
+        }
+    }
 }
 
+/// An error produced by the execution of an [`Operation`].
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Error {}
+pub struct Error {
+    /// The cause of the error.
+    cause: Cause,
+    /// The index of the operand responsible for the error, if any.
+    operand_idx: Option<usize>,
+}
 
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
-    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
-        Ok(())
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.cause.fmt(f)
+    }
+}
+
+impl Error {
+    pub fn new(cause: Cause) -> Self {
+        Self {
+            cause,
+            operand_idx: None,
+        }
+    }
+
+    /// Attributes this error to the operand at the given index.
+    pub fn at_operand(mut self, idx: usize) -> Self {
+        self.operand_idx = Some(idx);
+
+        self
     }
+
+    pub fn cause(&self) -> &Cause {
+        &self.cause
+    }
+
+    pub fn operand_idx(&self) -> Option<usize> {
+        self.operand_idx
+    }
+}
+
+/// The cause of an operation [error](Error).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Cause {
+    /// A divisor is zero.
+    DivisionByZero,
+    /// An operand lies outside the domain of the operation.
+    Domain {
+        /// Why the operand is outside the domain.
+        reason: &'static str,
+    },
+    /// An expression evaluated by the operation failed.
+    Eval(Box<Span<super::Error>>),
+    /// An expression value evaluated by the operation failed.
+    ///
+    /// Unlike those of [`Eval`](Self::Eval), such expressions may not have originated in the code
+    /// being evaluated, so errors are attributed to the operand that evaluated to the expression.
+    EvalValue(Box<super::Error>),
+    /// An iterative method did not converge to the required tolerance.
+    NoConvergence,
+    /// An operand is malformed.
+    Invalid {
+        /// Why the operand is malformed.
+        reason: &'static str,
+    },
+    /// The result is too large in magnitude to be represented.
+    Overflow,
+    /// The result cannot be represented without discarding significant digits.
+    PrecisionLoss,
+    /// Symbolic manipulation of an expression failed.
+    Symbolic(crate::sym::Error),
 }
 
-#[static_init::dynamic]
-pub static OPERATIONS: HashMap<&'static str, Operation, RandomState> = HashMap::from_iter([
+impl fmt::Display for Cause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => {
+                write!(f, "division by zero")
+            }
+            Self::Domain { reason } => {
+                write!(f, "domain error: {}", reason)
+            }
+            Self::Eval(e) => {
+                e.inner.fmt(f)
+            }
+            Self::EvalValue(e) => {
+                e.fmt(f)
+            }
+            Self::Invalid { reason } => {
+                write!(f, "invalid operand: {}", reason)
+            }
+            Self::NoConvergence => {
+                write!(f, "failed to converge")
+            }
+            Self::Overflow => {
+                write!(f, "overflow")
+            }
+            Self::PrecisionLoss => {
+                write!(f, "loss of precision")
+            }
+            Self::Symbolic(e) => {
+                e.fmt(f)
+            }
+        }
+    }
+}
+
+/// The built-in operations, with which every [`Environment`] starts.
+pub(crate) const BUILTINS: &[(&str, Operation)] = &[
+    ("let", var::LET),
+    ("defn", var::DEFN),
+    ("ans", var::ANS),
+    ("if", logic::IF),
+    ("eq", logic::EQ),
+    ("ne", logic::NE),
+    ("lt", logic::LT),
+    ("le", logic::LE),
+    ("gt", logic::GT),
+    ("ge", logic::GE),
     ("e", konst::E),
     ("pi", konst::PI),
+    ("i", konst::I),
     ("add", arith::ADD),
     ("sub", arith::SUB),
     ("mul", arith::MUL),
     ("div", arith::DIV),
-    // ("sum", arith::SUM),
-    // ("prod", arith::PROD),
-    // ("int", calc::INT),
+    ("min", arith::MIN),
+    ("max", arith::MAX),
+    ("pow", arith::POW),
+    ("sqrt", arith::SQRT),
+    ("exp", arith::EXP),
+    ("ln", arith::LN),
+    ("log", arith::LOG),
+    ("sum", arith::SUM),
+    ("prod", arith::PROD),
+    ("at", list::AT),
+    ("len", list::LEN),
+    ("concat", list::CONCAT),
+    ("transpose", matrix::TRANSPOSE),
+    ("matmul", matrix::MATMUL),
+    ("det", matrix::DET),
+    ("inv", matrix::INV),
+    ("rank", matrix::RANK),
+    ("linsolve", matrix::LINSOLVE),
+    ("lu", matrix::LU),
+    ("qr", matrix::QR),
+    ("eigen", matrix::EIGEN),
+    ("quote", expr::QUOTE),
+    ("eval", expr::EVAL),
+    ("simplify", expr::SIMPLIFY),
+    ("deriv", calc::DERIV),
+    ("int", calc::INT),
+    ("interr", calc::INTERR),
+    ("solve", calc::SOLVE),
+    ("plot", graph::PLOT),
     ("sin", trig::SIN),
     ("cos", trig::COS),
     ("tan", trig::TAN),
-    // ("arcsin", trig::ARCSIN),
-    // ("arccos", trig::ARCCOS),
-    // ("arctan", trig::ARCTAN),
-    // ("deg", trig::DEG),
-    // ("rad", trig::RAD),
-]);
+    ("arcsin", trig::ARCSIN),
+    ("arccos", trig::ARCCOS),
+    ("arctan", trig::ARCTAN),
+    ("atan2", trig::ATAN2),
+    ("abs", complex::ABS),
+    ("arg", trig::ARG),
+    ("conj", complex::CONJ),
+    ("re", complex::RE),
+    ("im", complex::IM),
+    ("polar", trig::POLAR),
+    ("rect", trig::RECT),
+    ("deg", trig::DEG),
+    ("rad", trig::RAD),
+    ("grad", trig::GRAD),
+    ("sinh", hyp::SINH),
+    ("cosh", hyp::COSH),
+    ("tanh", hyp::TANH),
+    ("arsinh", hyp::ARSINH),
+    ("arcosh", hyp::ARCOSH),
+    ("artanh", hyp::ARTANH),
+];
 
+/// Borrows the value of an operand of the given kind.
 macro_rules! access_operand {
-    ($parent:expr, Rational) => {
-        $parent.rational
+    ($operand:expr, Any) => {
+        $operand
     };
-    ($parent:expr, StrLit) => {
-        $parent.str_lit
+    ($operand:expr, Number) => {
+        $operand
+    };
+    ($operand:expr, $kind:ident) => {
+        match $operand {
+            $crate::evaluator::operand::Operand::$kind(it) => it,
+            // This `unreachable` is OK because operands are type-checked against the signature
+            // before the operation is executed.
+            _ => unreachable!("operand should match signature"),
+        }
     };
-    ($parent:expr, Symbol) => {
-        $parent.symbol
-    }
 }
 
 macro_rules! def_operation {
-    ($name:ident, [ $( $in_id:tt : $in_ty:ident ),* ], $out_op_ty:tt, $out:expr $(,)?) => {
+    // Operations that access the environment receive it as the first argument to `$out`.
+    ($name:ident, env, $($rest:tt)*) => {
+        def_operation!(@impl $name, [env, env], $($rest)*);
+    };
+    ($name:ident, $($rest:tt)*) => {
+        def_operation!(@impl $name, [_env], $($rest)*);
+    };
+    (
+        @impl $name:ident,
+        [ $env:ident $(, $pass_env:ident)? ],
+        [
+            $( $in_id:ident : $in_ty:ident $( = $default:expr )? ),*
+            $( , .. $rest_id:ident : $rest_ty:ident )? $(,)?
+        ],
+        $out_op_ty:tt,
+        $out:expr $(,)?
+    ) => {
         pub const $name: $crate::evaluator::operation::Operation = $crate::evaluator::operation::Operation {
-            sig: &[ $($crate::evaluator::operand::Kind::$in_ty),* ],
-            exe: |
+            sig: $crate::evaluator::operation::Sig {
+                params: std::borrow::Cow::Borrowed(&[
+                    $(
+                        $crate::evaluator::operation::Param {
+                            kind: $crate::evaluator::operand::Kind::$in_ty,
+                            default: def_operation!(@default $in_ty $(, $default)?),
+                        }
+                    ),*
+                ]),
+                variadic: def_operation!(@variadic $($rest_ty)?),
+            },
+            exe: $crate::evaluator::operation::Exe::Builtin(|
+                $env,
                 #[allow(unused_variables)]
                 ops
             | {
                 $(
-                    let $in_id = unsafe { &*access_operand!(&ops[0], $in_ty) };
+                    let $in_id = access_operand!(&ops[0], $in_ty);
                     // HACK: LOL.
                     #[allow(unused_variables)]
                     let ops = &ops[1..];
                 )*
+                $(
+                    let $rest_id: Vec<_> = ops
+                        .iter()
+                        .map(|op| access_operand!(op, $rest_ty))
+                        .collect();
+                )?
 
-                Ok($crate::evaluator::operation::Operand::$out_op_ty(
-                    $out( $($in_id),* )
-                ))
-            },
+                $out( $($pass_env,)? $($in_id),* $(, $rest_id)? )
+                    .map(def_operation!(@wrap $out_op_ty))
+            }),
         };
     };
+    // Operations whose output kind varies produce an `Operand` directly.
+    (@wrap Operand) => {
+        std::convert::identity
+    };
+    (@wrap $out_op_ty:ident) => {
+        $crate::evaluator::operation::Operand::$out_op_ty
+    };
+    (@default $in_ty:ident) => {
+        None
+    };
+    (@default Any, $default:expr) => {
+        Some(|| $default)
+    };
+    (@default Number, $default:expr) => {
+        Some(|| $default)
+    };
+    (@default $in_ty:ident, $default:expr) => {
+        Some(|| $crate::evaluator::operand::Operand::$in_ty($default))
+    };
+    (@variadic) => {
+        None
+    };
+    (@variadic $rest_ty:ident) => {
+        Some($crate::evaluator::operand::Kind::$rest_ty)
+    };
+}
+
+/// Evaluates an operand that was left unevaluated.
+fn eval(env: &mut Environment, operand: &Span<parser::Operand>) -> Result<Operand, Error> {
+    super::eval_quoted(operand, env).map_err(|e| Error::new(Cause::Eval(Box::new(e))))
+}
+
+/// Evaluates an expression value, attributing errors to the operand at the given index.
+fn eval_value(env: &mut Environment, idx: usize, expr: &Span<parser::Operand>) -> Result<Operand, Error> {
+    super::eval_quoted(expr, env)
+        .map_err(|e| Error::new(Cause::EvalValue(Box::new(e.inner.without_backtrace()))).at_operand(idx))
+}
+
+/// Evaluates the unevaluated operand at the given index, returning its value if it is a number.
+///
+/// An operand that evaluates to an expression, such as a derivative, is itself evaluated.
+fn eval_number(
+    env: &mut Environment,
+    idx: usize,
+    operand: &Span<parser::Operand>,
+) -> Result<Option<Number>, Error> {
+    let val = match eval(env, operand)? {
+        Operand::Expr(it) => eval_value(env, idx, &it)?,
+        it => it,
+    };
+
+    match val {
+        Operand::Rational(it) => Ok(Some(it.val)),
+        _ => Ok(None),
+    }
+}
+
+/// The operations that produce expressions or recall previous results.
+///
+/// These are evaluated, rather than manipulated, when they appear in an expression that is
+/// [expanded](expand).
+const SYMBOLIC: &[&str] = &["ans", "deriv", "quote", "simplify"];
+
+/// Prepares an unevaluated operand for symbolic manipulation, optionally with respect to a
+/// variable.
+///
+/// Names bound to expression values are replaced by those expressions, and nested symbolic
+/// operations are evaluated. The variable itself is left alone.
+fn expand(
+    env: &mut Environment,
+    operand: &Span<parser::Operand>,
+    var: Option<&str>,
+) -> Result<Span<parser::Operand>, Error> {
+    expand_impl(env, operand, var, &mut Vec::new())
+}
+
+fn expand_impl(
+    env: &mut Environment,
+    operand: &Span<parser::Operand>,
+    var: Option<&str>,
+    // The names being expanded, which are not expanded again within their own expansions.
+    expanding: &mut Vec<String>,
+) -> Result<Span<parser::Operand>, Error> {
+    if let Some(name) = crate::sym::as_name(operand) {
+        let bound = match env.lookup(name) {
+            Some(Operand::Expr(it)) if (Some(name) != var) && !expanding.iter().any(|it| it == name) => {
+                it.clone()
+            }
+            _ => return Ok(operand.clone()),
+        };
+
+        expanding.push(name.to_string());
+        let result = expand_impl(env, &bound, var, expanding);
+        let _ = expanding.pop();
+
+        return result;
+    }
+
+    let parser::Operand::Expr(expr) = &operand.inner else {
+        return Ok(operand.clone());
+    };
+
+    if SYMBOLIC.contains(&expr.operation.inner.name.as_str()) {
+        return Ok(match eval(env, operand)? {
+            Operand::Expr(it) => it,
+            Operand::Rational(it) => crate::sym::number(it.val, operand.range.clone()),
+            _ => operand.clone(),
+        });
+    }
+
+    let operands = expr
+        .operands
+        .iter()
+        .map(|it| expand_impl(env, it, var, expanding))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Span::new(
+        parser::Operand::Expr(parser::Expr {
+            operation: expr.operation.clone(),
+            operands,
+        }),
+        operand.range.clone(),
+    ))
+}
+
+/// Simplifies an expression, folding constant subexpressions by evaluating them.
+fn simplify(env: &mut Environment, expr: &Span<parser::Operand>) -> Span<parser::Operand> {
+    crate::sym::simplify(expr, &mut |node| match eval(env, node) {
+        Ok(Operand::Rational(it)) => Some(it.val),
+        _ => None,
+    })
+}
+
+/// Applies a function to the floating-point approximation of the operand at the given index.
+fn approx(idx: usize, a: &Rational, f: impl Fn(f64) -> f64) -> Result<Rational, Error> {
+    a.val
+        .map_approx(f)
+        .map(|val| Rational { val })
+        .ok_or_else(|| Error::new(Cause::Overflow).at_operand(idx))
+}
+
+/// Converts an operand of kind [`Number`](operand::Kind::Number) to a complex number.
+fn complex(num: &Operand) -> Complex {
+    // This `expect` is OK because operands are type-checked against the signature before the
+    // operation is executed.
+    Complex::from_operand(num).expect("operand should be a number")
+}
+
+/// Converts a one-based index, which counts backwards from the last item if negative, into a
+/// position among the given number of items.
+fn position(idx: i64, len: usize) -> Option<usize> {
+    match idx {
+        0 => None,
+        1.. => usize::try_from(idx - 1).ok().filter(|it| *it < len),
+        _ => usize::try_from(idx.unsigned_abs()).ok().and_then(|it| len.checked_sub(it)),
+    }
+}
+
+mod var {
+    use super::{position, Cause, Environment, Error};
+    use crate::{
+        evaluator::{env::Function, operand::Operand},
+        num::Number,
+        parser::{self, Rational, Symbol},
+        span::Span,
+    };
+
+    def_operation!(
+        LET,
+        env,
+        [name: Symbol, val: Any],
+        Operand,
+        |env: &mut Environment, name: &Symbol, val: &Operand| {
+            env.bind(name.name.clone(), val.clone());
+
+            Ok(val.clone())
+        },
+    );
+
+    def_operation!(
+        DEFN,
+        env,
+        [name: Symbol, params: Expr, body: Expr],
+        Symbol,
+        |env: &mut Environment, name: &Symbol, params: &Span<parser::Operand>, body: &Span<parser::Operand>| {
+            let params = match &params.inner {
+                // Empty parentheses are parsed as an empty list.
+                parser::Operand::List(it) if it.elems.is_empty() => Vec::new(),
+                parser::Operand::Expr(params) => {
+                    // The parameter list is parsed as an expression, so its first name is the
+                    // "operation".
+                    let first = params.operation.inner.name.clone();
+                    let rest = params.operands.iter().map(|operand| match &operand.inner {
+                        parser::Operand::Symbol(it) => Ok(it.name.clone()),
+                        _ => Err(Error::new(Cause::Invalid {
+                            reason: "function parameters must be symbols",
+                        })
+                        .at_operand(1)),
+                    });
+
+                    std::iter::once(Ok(first)).chain(rest).collect::<Result<_, _>>()?
+                }
+                _ => {
+                    return Err(Error::new(Cause::Invalid {
+                        reason: "function parameters must be enclosed in parentheses",
+                    })
+                    .at_operand(1));
+                }
+            };
+
+            env.define(name.name.clone(), Function { params, body: body.clone() });
+
+            Ok(name.clone())
+        },
+    );
+
+    def_operation!(
+        ANS,
+        env,
+        [idx: Rational = Rational { val: Number::from_integer(-1) }],
+        Operand,
+        |env: &mut Environment, idx: &Rational| {
+            let Some(idx) = idx.val.to_i64() else {
+                return Err(Error::new(Cause::Invalid { reason: "result numbers must be integers" }).at_operand(0));
+            };
+
+            let results = env.results();
+
+            position(idx, results.len())
+                .map(|it| results[it].clone())
+                .ok_or_else(|| Error::new(Cause::Domain { reason: "there is no such result" }).at_operand(0))
+        },
+    );
+}
+
+mod expr {
+    use super::{eval_value, expand, simplify, Environment};
+    use crate::{evaluator::operand::Operand, parser, span::Span};
+
+    def_operation!(
+        QUOTE,
+        [expr: Expr],
+        Expr,
+        |expr: &Span<parser::Operand>| Ok(expr.clone()),
+    );
+
+    def_operation!(
+        EVAL,
+        env,
+        [val: Any],
+        Operand,
+        |env: &mut Environment, val: &Operand| {
+            match val {
+                Operand::Expr(it) => eval_value(env, 0, it),
+                it => Ok(it.clone()),
+            }
+        },
+    );
+
+    def_operation!(
+        SIMPLIFY,
+        env,
+        [expr: Expr],
+        Expr,
+        |env: &mut Environment, expr: &Span<parser::Operand>| {
+            let expr = expand(env, expr, None)?;
+
+            Ok(simplify(env, &expr))
+        },
+    );
+}
+
+mod logic {
+    use super::{eval, Environment};
+    use crate::{
+        num::Number,
+        parser::{self, Rational},
+        span::Span,
+    };
+
+    def_operation!(
+        IF,
+        env,
+        [cond: Rational, then: Expr, otherwise: Expr],
+        Operand,
+        |env: &mut Environment, cond: &Rational, then: &Span<parser::Operand>, otherwise: &Span<parser::Operand>| {
+            // Only the chosen branch is evaluated.
+            if cond.val.is_zero() {
+                eval(env, otherwise)
+            } else {
+                eval(env, then)
+            }
+        },
+    );
+
+    /// Defines an operation that compares two operands, producing 1 if the comparison holds and 0
+    /// otherwise.
+    macro_rules! def_cmp {
+        ($name:ident, $op:tt) => {
+            def_operation!(
+                $name,
+                [a: Rational, b: Rational],
+                Rational,
+                |a: &Rational, b: &Rational| {
+                    Ok(Rational { val: Number::from_integer(i64::from(a.val $op b.val)) })
+                },
+            );
+        };
+    }
+
+    def_cmp!(EQ, ==);
+    def_cmp!(NE, !=);
+    def_cmp!(LT, <);
+    def_cmp!(LE, <=);
+    def_cmp!(GT, >);
+    def_cmp!(GE, >=);
 }
 
 mod konst {
-    use rust_decimal::Decimal;
+    use crate::num::{complex::Complex, Number};
 
     macro_rules! def_constant {
         ($name:ident, $val:expr) => {
@@ -87,55 +778,1968 @@ mod konst {
                 $name,
                 [],
                 Rational,
-                || crate::parser::Rational { val: $val },
+                || Ok(crate::parser::Rational { val: Number::Inexact($val) }),
             );
         };
     }
 
-    def_constant!(E, Decimal::E);
-    def_constant!(PI, Decimal::PI);
+    def_constant!(E, std::f64::consts::E);
+    def_constant!(PI, std::f64::consts::PI);
+
+    def_operation!(I, [], Complex, || Ok(Complex::i()));
 }
 
 mod arith {
-    use crate::parser::Rational;
+    use super::{approx, complex, eval_number, Cause, Environment, Error};
+    use crate::{
+        evaluator::operand::{IntoOperand as _, Operand},
+        num::{complex::Complex, Number},
+        parser::{self, Rational, Symbol},
+        span::Span,
+    };
 
-    macro_rules! def_infix_binary {
-        ($name:ident, $op:tt) => {
+    /// Folds a fallible binary function over operands from left to right.
+    ///
+    /// Errors are attributed to the operand being folded into the accumulator.
+    fn try_fold<'a>(
+        first: &Rational,
+        rest: impl IntoIterator<Item = &'a Rational>,
+        f: impl Fn(&Number, &Number) -> Result<Number, Cause>,
+    ) -> Result<Rational, Error> {
+        rest.into_iter()
+            .enumerate()
+            .try_fold(first.val.clone(), |acc, (idx, it)| {
+                f(&acc, &it.val).map_err(|cause| Error::new(cause).at_operand(idx + 1))
+            })
+            .map(|val| Rational { val })
+    }
+
+    /// Folds a fallible binary function over numbers from left to right, or its complex
+    /// counterpart if any of them is complex.
+    fn fold(
+        nums: &[&Operand],
+        f: impl Fn(&Number, &Number) -> Result<Number, Cause>,
+        complex_f: impl Fn(&Complex, &Complex) -> Result<Complex, Cause>,
+    ) -> Result<Operand, Error> {
+        if let Some(reals) = reals(nums) {
+            return try_fold(reals[0], reals[1..].iter().copied(), f).map(Operand::Rational);
+        }
+
+        nums[1..]
+            .iter()
+            .enumerate()
+            .try_fold(complex(nums[0]), |acc, (idx, it)| {
+                complex_f(&acc, &complex(it)).map_err(|cause| Error::new(cause).at_operand(idx + 1))
+            })
+            .map(Complex::into_operand)
+    }
+
+    /// The values of number operands if they are all real.
+    fn reals<'a>(nums: &[&'a Operand]) -> Option<Vec<&'a Rational>> {
+        nums.iter()
+            .map(|it| match it {
+                Operand::Rational(it) => Some(it),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Applies a function of numbers to operands, broadcasting it element-wise over lists.
+    ///
+    /// A number is paired with every element of a list, and lists are paired element by element,
+    /// so all lists must be of the same length. Lists may be nested. Errors are attributed to the
+    /// operand from which the offending number came.
+    fn broadcast(
+        operands: &[&Operand],
+        f: &dyn Fn(&[&Operand]) -> Result<Operand, Error>,
+    ) -> Result<Operand, Error> {
+        let mut len = None;
+        for (idx, operand) in operands.iter().enumerate() {
+            match operand {
+                Operand::Rational(_) | Operand::Complex(_) => {}
+                Operand::List(it) if len.is_none_or(|len| len == it.len()) => len = Some(it.len()),
+                Operand::List(_) => {
+                    return Err(Error::new(Cause::Invalid {
+                        reason: "lists must be of the same length",
+                    })
+                    .at_operand(idx));
+                }
+                _ => {
+                    return Err(Error::new(Cause::Invalid {
+                        reason: "expected a number or a list",
+                    })
+                    .at_operand(idx));
+                }
+            }
+        }
+
+        let Some(len) = len else {
+            return f(operands);
+        };
+
+        (0..len)
+            .map(|elem_idx| {
+                let elems: Vec<_> = operands
+                    .iter()
+                    .map(|it| match it {
+                        Operand::List(list) => &list[elem_idx],
+                        it => *it,
+                    })
+                    .collect();
+
+                broadcast(elems.as_slice(), f)
+            })
+            .collect::<Result<_, _>>()
+            .map(Operand::List)
+    }
+
+    /// Defines an operation that folds a fallible infix function, or its complex counterpart, over
+    /// its operands, broadcasting over lists.
+    ///
+    /// Operations declared `unary` accept one or more operands; all others accept two or more.
+    macro_rules! def_infix_fold {
+        (unary $name:ident, $fn:expr, $complex_fn:expr) => {
             def_operation!(
                 $name,
-                [a: Rational, b: Rational],
+                [a: Any, ..rest: Any],
+                Operand,
+                |a: &Operand, rest: Vec<&Operand>| {
+                    let operands: Vec<_> = std::iter::once(a).chain(rest).collect();
+                    broadcast(operands.as_slice(), &|nums| fold(nums, $fn, $complex_fn))
+                },
+            );
+        };
+        ($name:ident, $fn:expr, $complex_fn:expr) => {
+            def_operation!(
+                $name,
+                [a: Any, b: Any, ..rest: Any],
+                Operand,
+                |a: &Operand, b: &Operand, rest: Vec<&Operand>| {
+                    let operands: Vec<_> = [a, b].into_iter().chain(rest).collect();
+                    broadcast(operands.as_slice(), &|nums| fold(nums, $fn, $complex_fn))
+                },
+            );
+        };
+    }
+
+    def_infix_fold!(
+        unary ADD,
+        |a, b| a.checked_add(b).ok_or(Cause::Overflow),
+        |a, b| a.checked_add(b).ok_or(Cause::Overflow)
+    );
+    // Unlike the other infix operations, subtraction of a single operand, as in `(- x)`, negates it.
+    def_operation!(
+        SUB,
+        [a: Any, ..rest: Any],
+        Operand,
+        |a: &Operand, rest: Vec<&Operand>| {
+            if rest.is_empty() {
+                return broadcast(&[a], &|nums| Ok(complex(nums[0]).neg().into_operand()));
+            }
+
+            let operands: Vec<_> = std::iter::once(a).chain(rest).collect();
+            broadcast(operands.as_slice(), &|nums| {
+                fold(
+                    nums,
+                    |a, b| a.checked_sub(b).ok_or(Cause::Overflow),
+                    |a, b| a.checked_sub(b).ok_or(Cause::Overflow),
+                )
+            })
+        },
+    );
+    def_infix_fold!(
+        unary MUL,
+        |a, b| {
+            let product = a.checked_mul(b).ok_or(Cause::Overflow)?;
+            check_underflow(a, b, product)
+        },
+        |a, b| a.checked_mul(b).ok_or(Cause::Overflow)
+    );
+    def_infix_fold!(
+        DIV,
+        |a, b| {
+            if b.is_zero() {
+                return Err(Cause::DivisionByZero);
+            }
+
+            let quotient = a.checked_div(b).ok_or(Cause::Overflow)?;
+            check_underflow(a, b, quotient)
+        },
+        |a, b| {
+            if b.is_zero() {
+                return Err(Cause::DivisionByZero);
+            }
+
+            a.checked_div(b).ok_or(Cause::Overflow)
+        }
+    );
+
+    /// Fails if non-zero operands produced an inexact result that was rounded to zero.
+    fn check_underflow(a: &Number, b: &Number, result: Number) -> Result<Number, Cause> {
+        if result.is_zero() && !a.is_zero() && !b.is_zero() {
+            Err(Cause::PrecisionLoss)
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Defines an operation that selects one of one or more operands.
+    macro_rules! def_select {
+        ($name:ident, $cmp:tt) => {
+            def_operation!(
+                $name,
+                [a: Rational, ..rest: Rational],
+                Rational,
+                |a: &Rational, rest: Vec<&Rational>| {
+                    let selected = rest
+                        .into_iter()
+                        .fold(a, |acc, it| if it.val $cmp acc.val { it } else { acc });
+
+                    Ok(selected.clone())
+                },
+            );
+        };
+    }
+
+    def_select!(MIN, <);
+    def_select!(MAX, >);
+
+    /// Folds a fallible binary function over the values of a body expression as an index variable
+    /// ranges over the integers from `from` to `to`, inclusive.
+    ///
+    /// The index variable is bound in a new scope, which is discarded afterwards.
+    fn fold_range(
+        env: &mut Environment,
+        var: &Symbol,
+        from: &Rational,
+        to: &Rational,
+        body: &Span<parser::Operand>,
+        init: Number,
+        f: impl Fn(&Number, &Number) -> Result<Number, Cause>,
+    ) -> Result<Rational, Error> {
+        let bound = |idx: usize, it: &Rational| {
+            if !it.val.is_integer() {
+                return Err(Error::new(Cause::Invalid {
+                    reason: "range bounds must be integers",
+                })
+                .at_operand(idx));
+            }
+
+            it.val.to_i64().ok_or_else(|| Error::new(Cause::Overflow).at_operand(idx))
+        };
+        let from = bound(1, from)?;
+        let to = bound(2, to)?;
+
+        env.push_scope();
+        let result = (from..=to).try_fold(init, |acc, idx| {
+            env.bind(var.name.clone(), Operand::Rational(Rational { val: Number::from_integer(idx) }));
+
+            let Some(term) = eval_number(env, 3, body)? else {
+                return Err(Error::new(Cause::Invalid {
+                    reason: "the body must evaluate to a number",
+                })
+                .at_operand(3));
+            };
+
+            f(&acc, &term).map_err(|cause| Error::new(cause).at_operand(3))
+        });
+        env.pop_scope();
+
+        result.map(|val| Rational { val })
+    }
+
+    /// Defines a big operator, which folds a fallible infix function over a range.
+    macro_rules! def_big_operator {
+        ($name:ident, $init:expr, $fn:expr) => {
+            def_operation!(
+                $name,
+                env,
+                [var: Symbol, from: Rational, to: Rational, body: Expr],
                 Rational,
-                |a: &Rational, b: &Rational| Rational { val: a.val $op b.val },
+                |env: &mut Environment, var: &Symbol, from: &Rational, to: &Rational, body: &Span<parser::Operand>| {
+                    fold_range(env, var, from, to, body, Number::from_integer($init), $fn)
+                },
             );
         };
     }
 
-    def_infix_binary!(ADD, +);
-    def_infix_binary!(SUB, -);
-    def_infix_binary!(MUL, *);
-    def_infix_binary!(DIV, /);
+    // An empty range sums to 0 and multiplies to 1.
+    def_big_operator!(SUM, 0, |a, b| a.checked_add(b).ok_or(Cause::Overflow));
+    def_big_operator!(PROD, 1, |a, b| a.checked_mul(b).ok_or(Cause::Overflow));
+
+    def_operation!(
+        POW,
+        [base: Any, exp: Any],
+        Operand,
+        |base: &Operand, exp: &Operand| {
+            broadcast(&[base, exp], &|it| match reals(it).as_deref() {
+                // Negative numbers have complex non-integer powers.
+                Some([base, exp]) if !base.val.is_negative() || exp.val.is_integer() => {
+                    pow(base, exp).map(Operand::Rational)
+                }
+                _ => pow_complex(&complex(it[0]), &complex(it[1])),
+            })
+        },
+    );
+
+    fn pow(base: &Rational, exp: &Rational) -> Result<Rational, Error> {
+        if base.val.is_zero() && exp.val.is_negative() {
+            return Err(Error::new(Cause::DivisionByZero).at_operand(0));
+        }
+
+        if exp.val.is_integer() {
+            // Integer powers of exact numbers are exact.
+            return exp
+                .val
+                .to_i64()
+                .and_then(|exp| i32::try_from(exp).ok())
+                .and_then(|exp| base.val.checked_powi(exp))
+                .map(|val| Rational { val })
+                .ok_or_else(|| Error::new(Cause::Overflow).at_operand(1));
+        }
+
+        let exp = exp.val.to_f64();
+        approx(0, base, |base| base.powf(exp))
+    }
+
+    fn pow_complex(base: &Complex, exp: &Complex) -> Result<Operand, Error> {
+        if base.is_zero() {
+            if exp.re.is_negative() || exp.re.is_zero() {
+                return Err(Error::new(Cause::Domain {
+                    reason: "zero has no powers with a nonpositive real part",
+                })
+                .at_operand(1));
+            }
+
+            return Ok(Operand::Rational(Rational { val: Number::from_integer(0) }));
+        }
+
+        // Integer powers of exact numbers are exact.
+        let exact = exp
+            .is_real()
+            .then(|| exp.re.to_i64())
+            .flatten()
+            .and_then(|exp| i32::try_from(exp).ok());
+        let result = match exact {
+            Some(exp) => base.checked_powi(exp),
+            None => base.powc(exp),
+        };
+
+        result
+            .map(Complex::into_operand)
+            .ok_or_else(|| Error::new(Cause::Overflow).at_operand(1))
+    }
+
+    def_operation!(
+        SQRT,
+        [a: Any],
+        Operand,
+        |a: &Operand| {
+            broadcast(&[a], &|it| {
+                let root = match it[0] {
+                    Operand::Rational(a) if !a.val.is_negative() => {
+                        a.val.sqrt().map(|val| Operand::Rational(Rational { val }))
+                    }
+                    // Negative numbers have imaginary square roots.
+                    Operand::Rational(a) => a
+                        .val
+                        .neg()
+                        .sqrt()
+                        .map(|it| Complex::new(Number::from_integer(0), it).into_operand()),
+                    a => complex(a).sqrt().map(Complex::into_operand),
+                };
+
+                root.ok_or_else(|| Error::new(Cause::Overflow).at_operand(0))
+            })
+        },
+    );
+
+    def_operation!(
+        EXP,
+        [a: Any],
+        Operand,
+        |a: &Operand| {
+            broadcast(&[a], &|it| match it[0] {
+                Operand::Rational(a) => approx(0, a, f64::exp).map(Operand::Rational),
+                a => complex(a)
+                    .exp()
+                    .map(Complex::into_operand)
+                    .ok_or_else(|| Error::new(Cause::Overflow).at_operand(0)),
+            })
+        },
+    );
+
+    /// Takes the complex logarithm of the operand at the given index.
+    fn ln_complex(idx: usize, a: &Complex) -> Result<Complex, Error> {
+        if a.is_zero() {
+            return Err(Error::new(Cause::Domain {
+                reason: "the logarithm of zero is undefined",
+            })
+            .at_operand(idx));
+        }
+
+        a.ln().ok_or_else(|| Error::new(Cause::Overflow).at_operand(idx))
+    }
+
+    def_operation!(
+        LN,
+        [a: Any],
+        Operand,
+        |a: &Operand| {
+            // Negative numbers have complex logarithms.
+            broadcast(&[a], &|it| match it[0] {
+                Operand::Rational(a) if !(a.val.is_negative() || a.val.is_zero()) => {
+                    approx(0, a, f64::ln).map(Operand::Rational)
+                }
+                a => ln_complex(0, &complex(a)).map(Complex::into_operand),
+            })
+        },
+    );
+
+    def_operation!(
+        LOG,
+        [a: Any, base: Any = Operand::Rational(Rational { val: Number::from_integer(10) })],
+        Operand,
+        |a: &Operand, base: &Operand| {
+            broadcast(&[a, base], &|it| {
+                if complex(it[1]) == Complex::from_real(Number::from_integer(1)) {
+                    return Err(Error::new(Cause::Domain {
+                        reason: "logarithms are undefined in base 1",
+                    })
+                    .at_operand(1));
+                }
+
+                let positive = |it: &Rational| !(it.val.is_negative() || it.val.is_zero());
+                match reals(it).as_deref() {
+                    Some([a, base]) if positive(a) && positive(base) => log(a, base).map(Operand::Rational),
+                    _ => ln_complex(0, &complex(it[0]))?
+                        .checked_div(&ln_complex(1, &complex(it[1]))?)
+                        .map(Complex::into_operand)
+                        .ok_or_else(|| Error::new(Cause::Overflow).at_operand(0)),
+                }
+            })
+        },
+    );
+
+    /// Takes the logarithm of a positive number in a positive base other than 1.
+    fn log(a: &Rational, base: &Rational) -> Result<Rational, Error> {
+        let log = a.val.to_f64().ln() / base.val.to_f64().ln();
+
+        // If both operands are exact, the logarithm is exact when it is an integer; the
+        // approximation tells us which integer to try.
+        if a.val.is_exact() && base.val.is_exact() && log.is_finite() {
+            let exact = i32::try_from(log.round() as i64)
+                .ok()
+                .filter(|exp| base.val.checked_powi(*exp).as_ref() == Some(&a.val));
+            if let Some(exp) = exact {
+                return Ok(Rational { val: Number::from_integer(i64::from(exp)) });
+            }
+        }
+
+        Number::approx(log)
+            .map(|val| Rational { val })
+            .ok_or_else(|| Error::new(Cause::Overflow).at_operand(0))
+    }
+}
+
+mod list {
+    use super::{position, Cause, Error};
+    use crate::{evaluator::operand::Operand, num::Number, parser::Rational};
+    use std::rc::Rc;
+
+    def_operation!(
+        AT,
+        [list: List, idx: Rational],
+        Operand,
+        |list: &Rc<[Operand]>, idx: &Rational| {
+            let Some(idx) = idx.val.to_i64() else {
+                return Err(Error::new(Cause::Invalid { reason: "indices must be integers" }).at_operand(1));
+            };
+
+            // Elements are numbered from 1, and negative numbers count backwards from the last.
+            position(idx, list.len())
+                .map(|it| list[it].clone())
+                .ok_or_else(|| Error::new(Cause::Domain { reason: "the index is out of bounds" }).at_operand(1))
+        },
+    );
+
+    def_operation!(
+        LEN,
+        [list: List],
+        Rational,
+        |list: &Rc<[Operand]>| {
+            i64::try_from(list.len())
+                .map(|it| Rational { val: Number::from_integer(it) })
+                .map_err(|_| Error::new(Cause::Overflow).at_operand(0))
+        },
+    );
+
+    def_operation!(
+        CONCAT,
+        [a: List, ..rest: List],
+        List,
+        |a: &Rc<[Operand]>, rest: Vec<&Rc<[Operand]>>| {
+            Ok(a.iter().chain(rest.into_iter().flat_map(|it| it.iter())).cloned().collect())
+        },
+    );
+}
+
+mod matrix {
+    use super::{Cause, Environment, Error};
+    use crate::{
+        evaluator::operand::Operand,
+        num::{
+            matrix::{self, Matrix},
+            Number,
+        },
+        parser::Rational,
+    };
+    use std::rc::Rc;
+
+    /// Reads a matrix, which is written as a list of rows of numbers, from the operand at the given
+    /// index.
+    fn matrix(idx: usize, list: &[Operand]) -> Result<Matrix, Error> {
+        let expected = || {
+            Error::new(Cause::Invalid {
+                reason: "expected a matrix, which is a list of rows of numbers",
+            })
+            .at_operand(idx)
+        };
+
+        let cols = match list.first() {
+            Some(Operand::List(it)) if !it.is_empty() => it.len(),
+            _ => return Err(expected()),
+        };
+        let mut entries = Vec::with_capacity(list.len() * cols);
+        for row in list {
+            let Operand::List(row) = row else {
+                return Err(expected());
+            };
+            if row.len() != cols {
+                return Err(Error::new(Cause::Invalid {
+                    reason: "the rows of a matrix must be of the same length",
+                })
+                .at_operand(idx));
+            }
+
+            for entry in row.iter() {
+                let Operand::Rational(entry) = entry else {
+                    return Err(expected());
+                };
+                entries.push(entry.val.clone());
+            }
+        }
+
+        Ok(Matrix::new(list.len(), cols, entries))
+    }
+
+    /// Reads a square matrix from the operand at the given index.
+    fn square_matrix(idx: usize, list: &[Operand]) -> Result<Matrix, Error> {
+        let it = matrix(idx, list)?;
+        if !it.is_square() {
+            return Err(Error::new(Cause::Invalid {
+                reason: "the matrix must be square",
+            })
+            .at_operand(idx));
+        }
+
+        Ok(it)
+    }
+
+    /// Reads a matrix or a vector, which is a list of numbers read as a single column, from the
+    /// operand at the given index. Returns whether the operand was a vector.
+    fn matrix_or_vector(idx: usize, list: &[Operand]) -> Result<(Matrix, bool), Error> {
+        let entries: Option<Vec<_>> = list
+            .iter()
+            .map(|it| match it {
+                Operand::Rational(it) => Some(it.val.clone()),
+                _ => None,
+            })
+            .collect();
+
+        match entries {
+            Some(entries) if !entries.is_empty() => Ok((Matrix::new(entries.len(), 1, entries), true)),
+            _ => matrix(idx, list).map(|it| (it, false)),
+        }
+    }
+
+    fn number(val: Number) -> Operand {
+        Operand::Rational(Rational { val })
+    }
+
+    fn to_operand(it: &Matrix) -> Operand {
+        Operand::List(
+            it.iter_rows()
+                .map(|row| Operand::List(row.iter().cloned().map(number).collect()))
+                .collect(),
+        )
+    }
+
+    /// Converts a matrix to a vector if it was read from one, and to a list of rows otherwise.
+    fn to_operand_as(it: &Matrix, is_vector: bool) -> Operand {
+        if is_vector {
+            Operand::List(it.iter_rows().map(|row| number(row[0].clone())).collect())
+        } else {
+            to_operand(it)
+        }
+    }
+
+    /// Attributes a matrix error to the operand at the given index.
+    fn error(idx: usize, e: matrix::Error) -> Error {
+        let cause = match e {
+            matrix::Error::Overflow => Cause::Overflow,
+            matrix::Error::Singular => Cause::Domain {
+                reason: "the matrix is singular",
+            },
+            matrix::Error::Dependent => Cause::Domain {
+                reason: "the columns of the matrix are linearly dependent",
+            },
+        };
+
+        Error::new(cause).at_operand(idx)
+    }
+
+    def_operation!(
+        TRANSPOSE,
+        [a: List],
+        Operand,
+        |a: &Rc<[Operand]>| Ok(to_operand(&matrix(0, a)?.transpose())),
+    );
+
+    def_operation!(
+        MATMUL,
+        [a: List, b: List, ..rest: List],
+        Operand,
+        |a: &Rc<[Operand]>, b: &Rc<[Operand]>, rest: Vec<&Rc<[Operand]>>| {
+            let (mut product, mut is_vector) = matrix_or_vector(0, a)?;
+            for (idx, it) in std::iter::once(b).chain(rest).enumerate() {
+                let idx = idx + 1;
+                let (it, it_is_vector) = matrix_or_vector(idx, it)?;
+                if product.cols() != it.rows() {
+                    return Err(Error::new(Cause::Invalid {
+                        reason: "the matrix must have a row for each column of the previous product",
+                    })
+                    .at_operand(idx));
+                }
+
+                product = product.checked_mul(&it).map_err(|e| error(idx, e))?;
+                is_vector = it_is_vector;
+            }
+
+            Ok(to_operand_as(&product, is_vector))
+        },
+    );
+
+    def_operation!(
+        DET,
+        [a: List],
+        Rational,
+        |a: &Rc<[Operand]>| {
+            let val = square_matrix(0, a)?.det().map_err(|e| error(0, e))?;
+
+            Ok(Rational { val })
+        },
+    );
+
+    def_operation!(
+        INV,
+        [a: List],
+        Operand,
+        |a: &Rc<[Operand]>| {
+            let inverse = square_matrix(0, a)?.inverse().map_err(|e| error(0, e))?;
+
+            Ok(to_operand(&inverse))
+        },
+    );
+
+    def_operation!(
+        RANK,
+        [a: List],
+        Rational,
+        |a: &Rc<[Operand]>| {
+            let rank = matrix(0, a)?.rank().map_err(|e| error(0, e))?;
+
+            i64::try_from(rank)
+                .map(|it| Rational { val: Number::from_integer(it) })
+                .map_err(|_| Error::new(Cause::Overflow).at_operand(0))
+        },
+    );
+
+    def_operation!(
+        LINSOLVE,
+        [a: List, b: List],
+        Operand,
+        |a: &Rc<[Operand]>, b: &Rc<[Operand]>| {
+            let a = square_matrix(0, a)?;
+            let (b, is_vector) = matrix_or_vector(1, b)?;
+            if a.rows() != b.rows() {
+                return Err(Error::new(Cause::Invalid {
+                    reason: "the right-hand side must have a row for each row of the matrix",
+                })
+                .at_operand(1));
+            }
+
+            let x = a.solve(&b).map_err(|e| error(0, e))?;
+
+            Ok(to_operand_as(&x, is_vector))
+        },
+    );
+
+    def_operation!(
+        LU,
+        [a: List],
+        List,
+        |a: &Rc<[Operand]>| {
+            let lu = square_matrix(0, a)?.lu().map_err(|e| error(0, e))?;
+
+            Ok([&lu.p, &lu.l, &lu.u].into_iter().map(to_operand).collect())
+        },
+    );
+
+    def_operation!(
+        QR,
+        [a: List],
+        List,
+        |a: &Rc<[Operand]>| {
+            let qr = matrix(0, a)?.qr().map_err(|e| error(0, e))?;
+
+            Ok([&qr.q, &qr.r].into_iter().map(to_operand).collect())
+        },
+    );
+
+    def_operation!(
+        EIGEN,
+        env,
+        [a: List],
+        List,
+        |env: &mut Environment, a: &Rc<[Operand]>| {
+            let coefs = square_matrix(0, a)?.char_poly().map_err(|e| error(0, e))?;
+
+            // The eigenvalues are synthesized rather than derived from any part of the source code.
+            let roots = crate::sym::polynomial_roots(coefs, 0..0)
+                .ok_or_else(|| Error::new(Cause::Overflow).at_operand(0))?;
+
+            Ok(roots
+                .iter()
+                .map(|it| super::simplify(env, it))
+                .map(|it| match crate::sym::as_number(&it) {
+                    Some(val) => number(val.clone()),
+                    None => Operand::Expr(it),
+                })
+                .collect())
+        },
+    );
 }
 
 mod calc {
+    use super::{eval, eval_number, expand, Cause, Environment, Error};
+    use crate::{
+        evaluator::operand::Operand,
+        num::Number,
+        parser::{self, Rational, Symbol},
+        span::Span,
+        sym::Solution,
+    };
+
+    /// The maximum number of times the interval of integration is subdivided before integration is
+    /// considered not to converge.
+    const MAX_SUBDIVISIONS: usize = 1000;
+
+    /// The abscissae of the 15-point Kronrod rule on [-1, 1], in descending order. Odd indices are
+    /// also the abscissae of the 7-point Gauss rule.
+    const KRONROD_NODES: [f64; 8] = [
+        0.991_455_371_120_812_6,
+        0.949_107_912_342_758_5,
+        0.864_864_423_359_769_1,
+        0.741_531_185_599_394_4,
+        0.586_087_235_467_691_1,
+        0.405_845_151_377_397_2,
+        0.207_784_955_007_898_5,
+        0.0,
+    ];
+
+    /// The weights of the 15-point Kronrod rule.
+    const KRONROD_WEIGHTS: [f64; 8] = [
+        0.022_935_322_010_529_22,
+        0.063_092_092_629_978_55,
+        0.104_790_010_322_250_2,
+        0.140_653_259_715_525_9,
+        0.169_004_726_639_267_9,
+        0.190_350_578_064_785_4,
+        0.204_432_940_075_298_9,
+        0.209_482_141_084_727_8,
+    ];
+
+    /// The weights of the 7-point Gauss rule.
+    const GAUSS_WEIGHTS: [f64; 4] = [
+        0.129_484_966_168_869_7,
+        0.279_705_391_489_276_7,
+        0.381_830_050_505_118_9,
+        0.417_959_183_673_469_4,
+    ];
+
+    /// A bound of integration.
+    #[derive(Clone, Copy, PartialEq, PartialOrd)]
+    enum Bound {
+        NegInf,
+        Finite(f64),
+        PosInf,
+    }
+
+    /// Evaluates the operand at the given index as a bound of integration.
+    ///
+    /// The symbol `inf` and the expression `(- inf)` denote infinite bounds.
+    fn eval_bound(env: &mut Environment, idx: usize, operand: &Span<parser::Operand>) -> Result<Bound, Error> {
+        let is_inf = |operand: &parser::Operand| {
+            matches!(operand, parser::Operand::Symbol(it) if it.name == "inf")
+        };
+
+        match &operand.inner {
+            it if is_inf(it) => {
+                return Ok(Bound::PosInf);
+            }
+            parser::Operand::Expr(it)
+                if (it.operation.inner.name == "sub")
+                    && matches!(it.operands.as_slice(), [it] if is_inf(&it.inner)) =>
+            {
+                return Ok(Bound::NegInf);
+            }
+            _ => {}
+        }
+
+        match eval(env, operand)? {
+            Operand::Rational(it) => Ok(Bound::Finite(it.val.to_f64())),
+            _ => Err(Error::new(Cause::Invalid {
+                reason: "bounds of integration must be numbers",
+            })
+            .at_operand(idx)),
+        }
+    }
+
+    /// An estimate of an integral.
+    struct Estimate {
+        val: f64,
+        /// An estimate of the absolute error of `val`.
+        err: f64,
+        converged: bool,
+    }
+
+    /// Integrates a function over an interval with the 15-point Gauss--Kronrod rule.
+    ///
+    /// The difference between the Kronrod estimate and the embedded 7-point Gauss estimate serves as
+    /// the error estimate.
+    fn gauss_kronrod(
+        f: &mut impl FnMut(f64) -> Result<f64, Error>,
+        a: f64,
+        b: f64,
+    ) -> Result<(f64, f64), Error> {
+        let center = 0.5 * (a + b);
+        let half_len = 0.5 * (b - a);
+
+        let f_center = f(center)?;
+        let mut kronrod = f_center * KRONROD_WEIGHTS[7];
+        let mut gauss = f_center * GAUSS_WEIGHTS[3];
+        for (idx, node) in KRONROD_NODES[..7].iter().enumerate() {
+            let dx = half_len * node;
+            let pair = f(center - dx)? + f(center + dx)?;
+
+            kronrod += KRONROD_WEIGHTS[idx] * pair;
+            if idx % 2 == 1 {
+                gauss += GAUSS_WEIGHTS[idx / 2] * pair;
+            }
+        }
+
+        // The error estimate is no smaller than the rounding error of the estimate itself.
+        let val = kronrod * half_len;
+        let err = ((kronrod - gauss) * half_len).abs().max(50.0 * f64::EPSILON * val.abs());
+
+        Ok((val, err))
+    }
+
+    /// Integrates a function over a finite interval by repeatedly bisecting the subinterval with the
+    /// largest error estimate until the total error is within tolerance.
+    fn integrate_adaptive(
+        f: &mut impl FnMut(f64) -> Result<f64, Error>,
+        a: f64,
+        b: f64,
+        tolerance: f64,
+    ) -> Result<Estimate, Error> {
+        let (val, err) = gauss_kronrod(f, a, b)?;
+        let mut intervals = vec![(a, b, val, err)];
+
+        for _ in 0..MAX_SUBDIVISIONS {
+            let val: f64 = intervals.iter().map(|it| it.2).sum();
+            let err: f64 = intervals.iter().map(|it| it.3).sum();
+            if !(val.is_finite() && err.is_finite()) {
+                // The integral diverges.
+                break;
+            }
+            if err <= tolerance.max(tolerance * val.abs()) {
+                return Ok(Estimate { val, err, converged: true });
+            }
+
+            let (worst_idx, &(a, b, _, _)) = intervals
+                .iter()
+                .enumerate()
+                .max_by(|(_, x), (_, y)| x.3.total_cmp(&y.3))
+                // This `expect` is OK because there is always at least one interval.
+                .expect("there should be an interval");
+            let mid = 0.5 * (a + b);
+            if mid <= a || mid >= b {
+                // The interval cannot be bisected any further.
+                break;
+            }
+
+            let (left_val, left_err) = gauss_kronrod(f, a, mid)?;
+            let (right_val, right_err) = gauss_kronrod(f, mid, b)?;
+            intervals[worst_idx] = (a, mid, left_val, left_err);
+            intervals.push((mid, b, right_val, right_err));
+        }
+
+        Ok(Estimate {
+            val: intervals.iter().map(|it| it.2).sum(),
+            err: intervals.iter().map(|it| it.3).sum(),
+            converged: false,
+        })
+    }
+
+    /// Integrates a function between two bounds, either of which may be infinite.
+    ///
+    /// Infinite intervals are mapped onto finite ones by a change of variables. The integrand of a
+    /// convergent improper integral vanishes at infinity, so it is taken to be zero where rounding
+    /// places a node exactly at an infinite bound.
+    fn integrate(
+        f: &mut impl FnMut(f64) -> Result<f64, Error>,
+        from: Bound,
+        to: Bound,
+        tolerance: f64,
+    ) -> Result<Estimate, Error> {
+        if from == to {
+            return Ok(Estimate { val: 0.0, err: 0.0, converged: true });
+        }
+        if from > to {
+            return integrate(f, to, from, tolerance).map(|it| Estimate { val: -it.val, ..it });
+        }
+
+        match (from, to) {
+            (Bound::Finite(a), Bound::Finite(b)) => integrate_adaptive(f, a, b, tolerance),
+            (Bound::Finite(a), Bound::PosInf) => {
+                let mut g = |t: f64| {
+                    if t >= 1.0 {
+                        return Ok(0.0);
+                    }
+
+                    Ok(f(a + t / (1.0 - t))? / (1.0 - t).powi(2))
+                };
+                integrate_adaptive(&mut g, 0.0, 1.0, tolerance)
+            }
+            (Bound::NegInf, Bound::Finite(b)) => {
+                let mut g = |t: f64| {
+                    if t <= 0.0 {
+                        return Ok(0.0);
+                    }
+
+                    Ok(f(b - (1.0 - t) / t)? / t.powi(2))
+                };
+                integrate_adaptive(&mut g, 0.0, 1.0, tolerance)
+            }
+            _ => {
+                let mut g = |t: f64| {
+                    let denom = 1.0 - t * t;
+                    if denom <= 0.0 {
+                        return Ok(0.0);
+                    }
+
+                    Ok(f(t / denom)? * (1.0 + t * t) / denom.powi(2))
+                };
+                integrate_adaptive(&mut g, -1.0, 1.0, tolerance)
+            }
+        }
+    }
+
+    /// Estimates the integral of a body expression with respect to a variable.
+    ///
+    /// The variable is bound in a new scope, which is discarded afterwards.
+    fn estimate(
+        env: &mut Environment,
+        var: &Symbol,
+        from: &Span<parser::Operand>,
+        to: &Span<parser::Operand>,
+        body: &Span<parser::Operand>,
+        tolerance: &Rational,
+    ) -> Result<Estimate, Error> {
+        if tolerance.val.is_negative() || tolerance.val.is_zero() {
+            return Err(Error::new(Cause::Invalid {
+                reason: "the tolerance must be positive",
+            })
+            .at_operand(4));
+        }
+
+        let from = eval_bound(env, 1, from)?;
+        let to = eval_bound(env, 2, to)?;
+
+        env.push_scope();
+        let mut f = |x: f64| {
+            let x = Number::approx(x).ok_or_else(|| Error::new(Cause::Overflow).at_operand(3))?;
+            env.bind(var.name.clone(), Operand::Rational(Rational { val: x }));
+
+            match eval_number(env, 3, body)? {
+                Some(it) => Ok(it.to_f64()),
+                None => Err(Error::new(Cause::Invalid {
+                    reason: "the integrand must evaluate to a number",
+                })
+                .at_operand(3)),
+            }
+        };
+        let result = integrate(&mut f, from, to, tolerance.val.to_f64());
+        env.pop_scope();
+
+        result
+    }
+
+    /// The maximum number of iterations of Newton's method before falling back to bracketing.
+    const MAX_NEWTON_ITERATIONS: usize = 50;
+
+    /// The maximum number of iterations of Brent's method.
+    const MAX_BRENT_ITERATIONS: usize = 200;
+
+    /// The number of times the search for a sign change doubles its distance from the guess.
+    const MAX_BRACKET_EXPANSIONS: i32 = 64;
+
+    /// Finds a root of a function numerically, starting from a guess.
+    ///
+    /// Newton's method is tried first. Should it fail, the search expands outward from the guess
+    /// until the function changes sign, and the root within that bracket is found by Brent's method.
+    /// Points at which the function is undefined are skipped; if no root is found, the first error
+    /// encountered is returned.
+    fn find_root(f: &mut impl FnMut(f64) -> Result<f64, Error>, guess: f64) -> Result<f64, Error> {
+        let mut first_err = None;
+        let mut g = |x: f64| match f(x) {
+            Ok(it) if it.is_finite() => Some(it),
+            Ok(_) => None,
+            Err(e) => {
+                let _ = first_err.get_or_insert(e);
+                None
+            }
+        };
+
+        let root = newton(&mut g, guess).or_else(|| bracket(&mut g, guess));
+        match (root, first_err) {
+            (Some(it), _) => Ok(it),
+            (None, Some(e)) => Err(e),
+            (None, None) => Err(Error::new(Cause::NoConvergence)),
+        }
+    }
+
+    fn newton(g: &mut impl FnMut(f64) -> Option<f64>, guess: f64) -> Option<f64> {
+        let mut x = guess;
+        for _ in 0..MAX_NEWTON_ITERATIONS {
+            let fx = g(x)?;
+            if fx == 0.0 {
+                return Some(x);
+            }
+
+            // The derivative is approximated by a central difference.
+            let h = 1e-7 * x.abs().max(1.0);
+            let slope = (g(x + h)? - g(x - h)?) / (2.0 * h);
+            if (slope == 0.0) || !slope.is_finite() {
+                return None;
+            }
+
+            let next = x - (fx / slope);
+            if !next.is_finite() {
+                return None;
+            }
+            // Near an extremum that is not a root, the slope vanishes and steps grow, so a small
+            // step indicates a root.
+            if (next - x).abs() <= 1e-14 * next.abs().max(1.0) {
+                return Some(next);
+            }
+            x = next;
+        }
+
+        None
+    }
+
+    /// Searches outward from the guess for a sign change and finds the root within it.
+    fn bracket(g: &mut impl FnMut(f64) -> Option<f64>, guess: f64) -> Option<f64> {
+        let scale = 1e-2 * guess.abs().max(1.0);
+        // The nearest defined point on each side of the guess.
+        let mut prev = [g(guess).map(|it| (guess, it)); 2];
+
+        for k in 0..MAX_BRACKET_EXPANSIONS {
+            for (side, dir) in [-1.0, 1.0].into_iter().enumerate() {
+                let x = guess + dir * scale * 2f64.powi(k);
+                let Some(fx) = g(x) else {
+                    continue;
+                };
+                if fx == 0.0 {
+                    return Some(x);
+                }
+
+                if let Some((p, fp)) = prev[side] {
+                    if fp.signum() != fx.signum() {
+                        let root = brent(g, p, x, fp, fx)?;
+                        // A sign change may also be a pole, at which the function is not small.
+                        if g(root).is_some_and(|it| it.abs() <= fp.abs().min(fx.abs())) {
+                            return Some(root);
+                        }
+                    }
+                }
+                prev[side] = Some((x, fx));
+            }
+        }
+
+        None
+    }
+
+    /// Finds the root of a function within a bracket with Brent's method.
+    fn brent(
+        g: &mut impl FnMut(f64) -> Option<f64>,
+        mut a: f64,
+        mut b: f64,
+        mut fa: f64,
+        mut fb: f64,
+    ) -> Option<f64> {
+        let (mut c, mut fc) = (b, fb);
+        let (mut d, mut e) = (0.0, 0.0);
+
+        for _ in 0..MAX_BRENT_ITERATIONS {
+            if fb.signum() == fc.signum() {
+                c = a;
+                fc = fa;
+                d = b - a;
+                e = d;
+            }
+            if fc.abs() < fb.abs() {
+                a = b;
+                b = c;
+                c = a;
+                fa = fb;
+                fb = fc;
+                fc = fa;
+            }
+
+            let tol = 2.0 * f64::EPSILON * b.abs() + 0.5 * f64::MIN_POSITIVE;
+            let mid = 0.5 * (c - b);
+            if (mid.abs() <= tol) || (fb == 0.0) {
+                return Some(b);
+            }
+
+            if (e.abs() >= tol) && (fa.abs() > fb.abs()) {
+                // Attempt inverse quadratic interpolation, or the secant method if only two
+                // points are distinct.
+                let s = fb / fa;
+                let (mut p, mut q) = if a == c {
+                    (2.0 * mid * s, 1.0 - s)
+                } else {
+                    let q = fa / fc;
+                    let r = fb / fc;
+                    (
+                        s * (2.0 * mid * q * (q - r) - (b - a) * (r - 1.0)),
+                        (q - 1.0) * (r - 1.0) * (s - 1.0),
+                    )
+                };
+                if p > 0.0 {
+                    q = -q;
+                }
+                p = p.abs();
+
+                if 2.0 * p < (3.0 * mid * q - (tol * q).abs()).min((e * q).abs()) {
+                    e = d;
+                    d = p / q;
+                } else {
+                    // Interpolation failed; bisect instead.
+                    d = mid;
+                    e = d;
+                }
+            } else {
+                d = mid;
+                e = d;
+            }
+
+            a = b;
+            fa = fb;
+            b += if d.abs() > tol { d } else { tol.copysign(mid) };
+            fb = g(b)?;
+        }
+
+        None
+    }
+
+    /// The expression that is zero where an equation holds.
+    ///
+    /// This is `(sub lhs rhs)` for an equation `(eq lhs rhs)`; any other expression is taken to be
+    /// equal to zero.
+    fn equation_zero(eqn: &Span<parser::Operand>) -> Span<parser::Operand> {
+        match &eqn.inner {
+            parser::Operand::Expr(it) if (it.operation.inner.name == "eq") && (it.operands.len() == 2) => {
+                crate::sym::apply("sub", it.operands.clone(), eqn.range.clone())
+            }
+            _ => eqn.clone(),
+        }
+    }
 
+    def_operation!(
+        SOLVE,
+        env,
+        [var: Symbol, eqn: Expr, guess: Rational = Rational { val: Number::from_integer(0) }],
+        Operand,
+        |env: &mut Environment, var: &Symbol, eqn: &Span<parser::Operand>, guess: &Rational| {
+            let eqn = expand(env, eqn, Some(var.name.as_str()))?;
+            let zero = equation_zero(&eqn);
+
+            let solution = crate::sym::solve(&zero, var.name.as_str(), &mut |node| match eval(env, node) {
+                Ok(Operand::Rational(it)) => Some(it.val),
+                _ => None,
+            })
+            .map_err(|e| Error::new(Cause::Symbolic(e)).at_operand(1))?;
+            match solution {
+                Some(Solution::Identity) => {
+                    return Err(Error::new(Cause::Invalid {
+                        reason: "every value is a solution",
+                    })
+                    .at_operand(1));
+                }
+                Some(Solution::Contradiction) => {
+                    return Err(Error::new(Cause::Domain {
+                        reason: "the equation has no solutions",
+                    })
+                    .at_operand(1));
+                }
+                Some(Solution::Roots(roots)) => {
+                    let mut roots: Vec<_> = roots.iter().map(|it| super::simplify(env, it)).collect();
+
+                    return match roots.len() {
+                        // Every root of a linear or quadratic equation is found, so roots are only
+                        // missed where they are searched for numerically.
+                        0 => Err(Error::new(Cause::Domain {
+                            reason: "no real solutions found",
+                        })
+                        .at_operand(1)),
+                        1 => Ok(match crate::sym::as_number(&roots[0]) {
+                            Some(it) => Operand::Rational(Rational { val: it.clone() }),
+                            None => Operand::Expr(roots.remove(0)),
+                        }),
+                        // Multiple solutions are collected into a `roots` expression.
+                        _ => Ok(Operand::Expr(crate::sym::apply("roots", roots, eqn.range.clone()))),
+                    };
+                }
+                None => {}
+            }
+
+            // The equation is not a polynomial or rational one, so it is solved numerically.
+            env.push_scope();
+            let mut f = |x: f64| {
+                let x = Number::approx(x).ok_or_else(|| Error::new(Cause::Overflow).at_operand(1))?;
+                env.bind(var.name.clone(), Operand::Rational(Rational { val: x }));
+
+                match eval_number(env, 1, &zero)? {
+                    Some(it) => Ok(it.to_f64()),
+                    None => Err(Error::new(Cause::Invalid {
+                        reason: "the equation must evaluate to a number",
+                    })
+                    .at_operand(1)),
+                }
+            };
+            let result = find_root(&mut f, guess.val.to_f64()).and_then(|root| {
+                // Roots that are nearly integers are checked exactly, as in `(solve x (= (tan x) 0))`.
+                let rounded = root.round();
+                if (root - rounded).abs() <= 1e-9 * root.abs().max(1.0) {
+                    let exact = Number::approx(rounded)
+                        .and_then(|it| it.to_i64())
+                        .map(Number::from_integer);
+                    if let Some(exact) = exact {
+                        env.bind(var.name.clone(), Operand::Rational(Rational { val: exact.clone() }));
+                        if eval_number(env, 1, &zero)?.is_some_and(|it| it.is_exact() && it.is_zero()) {
+                            return Ok(exact);
+                        }
+                    }
+                }
+
+                Number::approx(root).ok_or_else(|| Error::new(Cause::Overflow))
+            });
+            env.pop_scope();
+
+            result.map(|val| Operand::Rational(Rational { val }))
+        },
+    );
+
+    def_operation!(
+        DERIV,
+        env,
+        [var: Symbol, body: Expr],
+        Expr,
+        |env: &mut Environment, var: &Symbol, body: &Span<parser::Operand>| {
+            let body = expand(env, body, Some(var.name.as_str()))?;
+            let result = crate::sym::deriv(&body, var.name.as_str(), env.angle_mode())
+                .map_err(|e| Error::new(Cause::Symbolic(e)).at_operand(1))?;
+
+            Ok(super::simplify(env, &result))
+        },
+    );
+
+    def_operation!(
+        INT,
+        env,
+        [
+            var: Symbol,
+            from: Expr,
+            to: Expr,
+            body: Expr,
+            tolerance: Rational = Rational { val: Number::from_ratio(1, 10_000_000_000) },
+        ],
+        Rational,
+        |env: &mut Environment, var: &Symbol, from: &Span<parser::Operand>, to: &Span<parser::Operand>, body: &Span<parser::Operand>, tolerance: &Rational| {
+            let estimate = estimate(env, var, from, to, body, tolerance)?;
+            if !estimate.converged {
+                // `interr` may be used to inspect the estimate that failed to converge.
+                return Err(Error::new(Cause::NoConvergence));
+            }
+
+            Number::approx(estimate.val)
+                .map(|val| Rational { val })
+                .ok_or_else(|| Error::new(Cause::Overflow))
+        },
+    );
+
+    def_operation!(
+        INTERR,
+        env,
+        [
+            var: Symbol,
+            from: Expr,
+            to: Expr,
+            body: Expr,
+            tolerance: Rational = Rational { val: Number::from_ratio(1, 10_000_000_000) },
+        ],
+        Rational,
+        |env: &mut Environment, var: &Symbol, from: &Span<parser::Operand>, to: &Span<parser::Operand>, body: &Span<parser::Operand>, tolerance: &Rational| {
+            let estimate = estimate(env, var, from, to, body, tolerance)?;
+
+            Number::approx(estimate.err)
+                .map(|val| Rational { val })
+                .ok_or_else(|| Error::new(Cause::Overflow))
+        },
+    );
+}
+
+mod complex {
+    use super::{complex, Cause, Error};
+    use crate::{
+        evaluator::operand::{IntoOperand as _, Operand},
+        parser::Rational,
+    };
+
+    def_operation!(
+        ABS,
+        [z: Number],
+        Rational,
+        |z: &Operand| {
+            complex(z)
+                .abs()
+                .map(|val| Rational { val })
+                .ok_or_else(|| Error::new(Cause::Overflow).at_operand(0))
+        },
+    );
+
+    def_operation!(
+        CONJ,
+        [z: Number],
+        Operand,
+        |z: &Operand| Ok(complex(z).conj().into_operand()),
+    );
+
+    def_operation!(
+        RE,
+        [z: Number],
+        Rational,
+        |z: &Operand| Ok(Rational { val: complex(z).re }),
+    );
+
+    def_operation!(
+        IM,
+        [z: Number],
+        Rational,
+        |z: &Operand| Ok(Rational { val: complex(z).im }),
+    );
 }
 
 mod trig {
-    use crate::parser::Rational;
-    use rust_decimal::MathematicalOps as _;
+    use super::{approx, complex, Cause, Error};
+    use crate::{
+        evaluator::{
+            env::AngleMode,
+            operand::{IntoOperand as _, Operand},
+            Environment,
+        },
+        num::{complex::Complex, Number},
+        parser::Rational,
+    };
+    use std::rc::Rc;
+    use std::f64::consts::TAU;
+
+    /// Converts an angle from one unit to another.
+    ///
+    /// Conversions between degrees and gradians are exact. Returns `None` on overflow.
+    fn convert(a: &Number, from: AngleMode, to: AngleMode) -> Option<Number> {
+        if from == to {
+            return Some(a.clone());
+        }
+
+        match (from.turn(), to.turn()) {
+            (Some(from), Some(to)) => a.checked_mul(&Number::from_ratio(to, from)),
+            (from, to) => {
+                let from = from.map_or(TAU, |it| it as f64);
+                let to = to.map_or(TAU, |it| it as f64);
+
+                a.map_approx(|a| a / from * to)
+            }
+        }
+    }
+
+    /// Converts the operand at the given index, an angle in the current angle mode, to radians.
+    fn to_radians(idx: usize, a: &Rational, mode: AngleMode) -> Result<Rational, Error> {
+        convert(&a.val, mode, AngleMode::Radians)
+            .map(|val| Rational { val })
+            .ok_or_else(|| Error::new(Cause::Overflow).at_operand(idx))
+    }
+
+    /// Converts an exact fraction of a turn to the current angle mode.
+    fn from_turns(turns: Number, mode: AngleMode) -> Rational {
+        let val = match mode.turn() {
+            // This `expect` is OK because fractions of a turn are small.
+            Some(turn) => turns.checked_mul(&Number::from_integer(turn)).expect("angle should be small"),
+            None => Number::Inexact(turns.to_f64() * TAU),
+        };
 
+        Rational { val }
+    }
+
+    /// Converts the result of an inverse function, an angle in radians, to the current angle mode.
+    fn from_radians(a: f64, mode: AngleMode) -> Result<Rational, Error> {
+        Number::approx(a)
+            .and_then(|a| convert(&a, AngleMode::Radians, mode))
+            .map(|val| Rational { val })
+            .ok_or_else(|| Error::new(Cause::Overflow))
+    }
+
+    /// Applies a complex function to the operand at the given index, a complex angle in the current
+    /// angle mode.
+    fn complex_trig(
+        idx: usize,
+        a: &Complex,
+        mode: AngleMode,
+        f: impl Fn(&Complex) -> Option<Complex>,
+    ) -> Result<Operand, Error> {
+        // Both parts are converted to radians.
+        let radians = convert(&Number::from_integer(1), mode, AngleMode::Radians)
+            .and_then(|it| a.checked_mul(&Complex::from_real(it)))
+            .ok_or_else(|| Error::new(Cause::Overflow).at_operand(idx))?;
+
+        f(&radians)
+            .map(Complex::into_operand)
+            .ok_or_else(|| Error::new(Cause::Overflow).at_operand(idx))
+    }
+
+    /// A numerator and denominator.
+    type Fraction = (i64, i64);
+
+    /// The number of `1/parts` turns in an angle, modulo `parts`, if it is an exact multiple of such a
+    /// fraction of a turn in the current angle mode.
+    fn fractional_turns(a: &Number, mode: AngleMode, parts: i64) -> Option<usize> {
+        if a.is_zero() {
+            return Some(0);
+        }
+
+        let count = a.checked_div(&Number::from_ratio(mode.turn()?, parts))?;
+        if !(count.is_exact() && count.is_integer()) {
+            return None;
+        }
+
+        usize::try_from(count.to_i64()?.rem_euclid(parts)).ok()
+    }
+
+    /// Defines a trigonometric function of an angle in the current angle mode, which may be complex.
+    ///
+    /// By Niven's theorem, the sine and cosine of a rational fraction of a turn are rational only at
+    /// multiples of a twelfth of a turn, where they take the given exact values.
     macro_rules! def_trig_fn {
-        ($name:ident, $fn:ident) => {
+        ($name:ident, $fn:path, $complex_fn:path, $at_twelfths:expr) => {
             def_operation!(
                 $name,
+                env,
+                [a: Number],
+                Operand,
+                |env: &mut Environment, a: &Operand| {
+                    let at_twelfths: [Option<Fraction>; 12] = $at_twelfths;
+
+                    let mode = env.angle_mode();
+                    let Operand::Rational(a) = a else {
+                        return complex_trig(0, &complex(a), mode, $complex_fn);
+                    };
+
+                    let exact = fractional_turns(&a.val, mode, 12).and_then(|it| at_twelfths[it]);
+                    if let Some((numer, denom)) = exact {
+                        return Ok(Operand::Rational(Rational { val: Number::from_ratio(numer, denom) }));
+                    }
+
+                    approx(0, &to_radians(0, a, mode)?, $fn).map(Operand::Rational)
+                },
+            );
+        };
+    }
+
+    def_trig_fn!(
+        SIN,
+        f64::sin,
+        Complex::sin,
+        [
+            Some((0, 1)),
+            Some((1, 2)),
+            None,
+            Some((1, 1)),
+            None,
+            Some((1, 2)),
+            Some((0, 1)),
+            Some((-1, 2)),
+            None,
+            Some((-1, 1)),
+            None,
+            Some((-1, 2)),
+        ]
+    );
+    def_trig_fn!(
+        COS,
+        f64::cos,
+        Complex::cos,
+        [
+            Some((1, 1)),
+            None,
+            Some((1, 2)),
+            Some((0, 1)),
+            Some((-1, 2)),
+            None,
+            Some((-1, 1)),
+            None,
+            Some((-1, 2)),
+            Some((0, 1)),
+            Some((1, 2)),
+            None,
+        ]
+    );
+
+    def_operation!(
+        TAN,
+        env,
+        [a: Number],
+        Operand,
+        |env: &mut Environment, a: &Operand| {
+            let mode = env.angle_mode();
+            let Operand::Rational(a) = a else {
+                return complex_trig(0, &complex(a), mode, Complex::tan);
+            };
+
+            let undefined = || {
+                Error::new(Cause::Domain {
+                    reason: "the tangent is undefined at odd multiples of a quarter turn",
+                })
+                .at_operand(0)
+            };
+
+            // The tangent of a rational fraction of a turn is rational only at multiples of an eighth
+            // of a turn.
+            if let Some(eighths) = fractional_turns(&a.val, mode, 8) {
+                let val = match eighths % 4 {
+                    0 => 0,
+                    1 => 1,
+                    2 => return Err(undefined()),
+                    _ => -1,
+                };
+
+                return Ok(Operand::Rational(Rational { val: Number::from_integer(val) }));
+            }
+
+            let radians = to_radians(0, a, mode)?;
+            let a_approx = radians.val.to_f64();
+
+            // Operands such as pi/2 are only approximations, so they never exactly coincide with the
+            // poles of the tangent; anything within rounding error of a pole is considered to lie on
+            // it.
+            let tolerance = f64::EPSILON * a_approx.abs().max(1.);
+            if a_approx.cos().abs() <= tolerance {
+                return Err(undefined());
+            }
+
+            approx(0, &radians, f64::tan).map(Operand::Rational)
+        },
+    );
+
+    /// Looks up the angle, as an exact fraction of a turn, to which an inverse function maps an exact
+    /// operand.
+    ///
+    /// By Niven's theorem, the only rational sines and cosines of rational fractions of a turn are 0,
+    /// ±1/2, and ±1, and the only rational tangents are 0 and ±1, so these tables are complete.
+    fn exact_inverse(a: &Number, table: &[(Fraction, Fraction)]) -> Option<Number> {
+        if !a.is_exact() {
+            return None;
+        }
+
+        table
+            .iter()
+            .find(|((numer, denom), _)| *a == Number::from_ratio(*numer, *denom))
+            .map(|(_, (numer, denom))| Number::from_ratio(*numer, *denom))
+    }
+
+    /// Determines if a number lies within the closed interval from -1 to 1.
+    fn in_unit_interval(a: &Number) -> bool {
+        a.abs() <= Number::from_integer(1)
+    }
+
+    /// Defines an inverse trigonometric function, which produces an angle in the current angle mode.
+    ///
+    /// Operands for which there is no real angle, including complex ones, produce complex angles;
+    /// `undefined` is the cause of failure where the complex function is undefined.
+    macro_rules! def_inverse_trig_fn {
+        ($name:ident, $fn:path, $complex_fn:path, $has_real:expr, $undefined:expr, $table:expr) => {
+            def_operation!(
+                $name,
+                env,
+                [a: Number],
+                Operand,
+                |env: &mut Environment, a: &Operand| {
+                    let has_real: fn(&Number) -> bool = $has_real;
+                    let undefined: fn() -> Cause = $undefined;
+
+                    let mode = env.angle_mode();
+                    let a = match a {
+                        Operand::Rational(a) if has_real(&a.val) => a,
+                        a => {
+                            // Both parts are converted from radians.
+                            let angle = $complex_fn(&complex(a)).ok_or_else(|| Error::new(undefined()).at_operand(0))?;
+                            return convert(&Number::from_integer(1), AngleMode::Radians, mode)
+                                .and_then(|it| angle.checked_mul(&Complex::from_real(it)))
+                                .map(Complex::into_operand)
+                                .ok_or_else(|| Error::new(Cause::Overflow).at_operand(0));
+                        }
+                    };
+
+                    if let (Some(_), Some(turns)) = (mode.turn(), exact_inverse(&a.val, $table)) {
+                        return Ok(Operand::Rational(from_turns(turns, mode)));
+                    }
+
+                    from_radians($fn(a.val.to_f64()), mode)
+                        .map(Operand::Rational)
+                        .map_err(|e| e.at_operand(0))
+                },
+            );
+        };
+    }
+
+    def_inverse_trig_fn!(
+        ARCSIN,
+        f64::asin,
+        Complex::asin,
+        in_unit_interval,
+        || Cause::Overflow,
+        &[
+            ((-1, 1), (-1, 4)),
+            ((-1, 2), (-1, 12)),
+            ((0, 1), (0, 1)),
+            ((1, 2), (1, 12)),
+            ((1, 1), (1, 4)),
+        ]
+    );
+    def_inverse_trig_fn!(
+        ARCCOS,
+        f64::acos,
+        Complex::acos,
+        in_unit_interval,
+        || Cause::Overflow,
+        &[
+            ((-1, 1), (1, 2)),
+            ((-1, 2), (1, 3)),
+            ((0, 1), (1, 4)),
+            ((1, 2), (1, 6)),
+            ((1, 1), (0, 1)),
+        ]
+    );
+    def_inverse_trig_fn!(
+        ARCTAN,
+        f64::atan,
+        Complex::atan,
+        |_| true,
+        || Cause::Domain {
+            reason: "the arctangent is undefined at i and -i",
+        },
+        &[((-1, 1), (-1, 8)), ((0, 1), (0, 1)), ((1, 1), (1, 8))]
+    );
+
+    /// Finds the angle of the point `(x, y)` from the positive x-axis, in the current angle mode.
+    fn angle(y: &Number, x: &Number, mode: AngleMode) -> Result<Rational, Error> {
+        if y.is_zero() && x.is_zero() {
+            return Err(Error::new(Cause::Domain {
+                reason: "the angle of the origin is undefined",
+            }));
+        }
+
+        // Angles on the axes and diagonals are exact fractions of a turn.
+        if mode.turn().is_some() && y.is_exact() && x.is_exact() {
+            let turns = if y.is_zero() {
+                Some(if x.is_negative() { (1, 2) } else { (0, 1) })
+            } else if x.is_zero() {
+                Some(if y.is_negative() { (-1, 4) } else { (1, 4) })
+            } else if y.abs() == x.abs() {
+                Some(match (y.is_negative(), x.is_negative()) {
+                    (false, false) => (1, 8),
+                    (false, true) => (3, 8),
+                    (true, false) => (-1, 8),
+                    (true, true) => (-3, 8),
+                })
+            } else {
+                None
+            };
+
+            if let Some((numer, denom)) = turns {
+                return Ok(from_turns(Number::from_ratio(numer, denom), mode));
+            }
+        }
+
+        from_radians(y.to_f64().atan2(x.to_f64()), mode)
+    }
+
+    def_operation!(
+        ATAN2,
+        env,
+        [y: Rational, x: Rational],
+        Rational,
+        |env: &mut Environment, y: &Rational, x: &Rational| angle(&y.val, &x.val, env.angle_mode()),
+    );
+
+    def_operation!(
+        ARG,
+        env,
+        [z: Number],
+        Rational,
+        |env: &mut Environment, z: &Operand| {
+            let z = complex(z);
+            angle(&z.im, &z.re, env.angle_mode()).map_err(|e| e.at_operand(0))
+        },
+    );
+
+    def_operation!(
+        POLAR,
+        env,
+        [z: Number],
+        List,
+        |env: &mut Environment, z: &Operand| {
+            let z = complex(z);
+            let abs = z.abs().ok_or_else(|| Error::new(Cause::Overflow).at_operand(0))?;
+            let arg = angle(&z.im, &z.re, env.angle_mode()).map_err(|e| e.at_operand(0))?;
+
+            Ok(Rc::from([Operand::Rational(Rational { val: abs }), Operand::Rational(arg)]))
+        },
+    );
+
+    def_operation!(
+        RECT,
+        env,
+        [abs: Rational, arg: Rational],
+        Operand,
+        |env: &mut Environment, abs: &Rational, arg: &Rational| {
+            // Both the sine and cosine of a rational fraction of a turn are rational only at
+            // multiples of a quarter turn.
+            let mode = env.angle_mode();
+            if let Some(quarters) = fractional_turns(&arg.val, mode, 4) {
+                let (zero, one) = (Number::from_integer(0), Number::from_integer(1));
+                let unit = match quarters {
+                    0 => Complex::new(one, zero),
+                    1 => Complex::new(zero, one),
+                    2 => Complex::new(one.neg(), zero),
+                    _ => Complex::new(zero, one.neg()),
+                };
+
+                return unit
+                    .checked_mul(&Complex::from_real(abs.val.clone()))
+                    .map(Complex::into_operand)
+                    .ok_or_else(|| Error::new(Cause::Overflow).at_operand(0));
+            }
+
+            let arg = to_radians(1, arg, mode)?;
+            Complex::from_polar(abs.val.to_f64(), arg.val.to_f64())
+                .map(Complex::into_operand)
+                .ok_or_else(|| Error::new(Cause::Overflow).at_operand(0))
+        },
+    );
+
+    /// Defines an operation that converts an angle from the given unit to the current angle mode.
+    macro_rules! def_angle_conversion {
+        ($name:ident, $from:ident) => {
+            def_operation!(
+                $name,
+                env,
                 [a: Rational],
                 Rational,
-                |a: &Rational| Rational { val: a.val.$fn() },
+                |env: &mut Environment, a: &Rational| {
+                    convert(&a.val, AngleMode::$from, env.angle_mode())
+                        .map(|val| Rational { val })
+                        .ok_or_else(|| Error::new(Cause::Overflow).at_operand(0))
+                },
             );
         };
     }
 
-    def_trig_fn!(SIN, sin);
-    def_trig_fn!(COS, cos);
-    def_trig_fn!(TAN, tan);
+    def_angle_conversion!(DEG, Degrees);
+    def_angle_conversion!(RAD, Radians);
+    def_angle_conversion!(GRAD, Gradians);
+}
+
+mod hyp {
+    use super::{approx, complex, Cause, Error};
+    use crate::{
+        evaluator::operand::{IntoOperand as _, Operand},
+        num::{complex::Complex, Number},
+    };
+
+    /// Applies a complex function to the operand at the given index.
+    fn complex_hyp(
+        idx: usize,
+        a: &Operand,
+        f: impl Fn(&Complex) -> Option<Complex>,
+        undefined: Cause,
+    ) -> Result<Operand, Error> {
+        f(&complex(a)).map(Complex::into_operand).ok_or_else(|| Error::new(undefined).at_operand(idx))
+    }
+
+    /// Defines a hyperbolic function, or an inverse one, which may be complex.
+    ///
+    /// Real operands for which `has_real` holds produce real results, and all others complex ones.
+    macro_rules! def_hyp_fn {
+        ($name:ident, $fn:path, $complex_fn:path) => {
+            def_hyp_fn!($name, $fn, $complex_fn, |_| true, Cause::Overflow);
+        };
+        ($name:ident, $fn:path, $complex_fn:path, $has_real:expr, $undefined:expr) => {
+            def_operation!(
+                $name,
+                [a: Number],
+                Operand,
+                |a: &Operand| {
+                    let has_real: fn(&Number) -> bool = $has_real;
+
+                    match a {
+                        Operand::Rational(a) if has_real(&a.val) => approx(0, a, $fn).map(Operand::Rational),
+                        a => complex_hyp(0, a, $complex_fn, $undefined),
+                    }
+                },
+            );
+        };
+    }
+
+    def_hyp_fn!(SINH, f64::sinh, Complex::sinh);
+    def_hyp_fn!(COSH, f64::cosh, Complex::cosh);
+    def_hyp_fn!(TANH, f64::tanh, Complex::tanh);
+    def_hyp_fn!(ARSINH, f64::asinh, Complex::asinh);
+    def_hyp_fn!(
+        ARCOSH,
+        f64::acosh,
+        Complex::acosh,
+        |a| *a >= Number::from_integer(1),
+        Cause::Overflow
+    );
+    def_hyp_fn!(
+        ARTANH,
+        f64::atanh,
+        Complex::atanh,
+        |a| a.abs() < Number::from_integer(1),
+        Cause::Domain {
+            reason: "the inverse hyperbolic tangent is undefined at 1 and -1",
+        }
+    );
+}
+
+mod graph {
+    use super::{eval_number, Cause, Environment, Error};
+    use crate::{
+        evaluator::{
+            graphic::{Graphic, Series},
+            operand::Operand,
+        },
+        num::Number,
+        parser::{self, Rational, Symbol},
+        span::Span,
+    };
+    use std::rc::Rc;
+
+    /// The number of points at which each function is sampled.
+    const SAMPLES: usize = 512;
+
+    def_operation!(
+        PLOT,
+        env,
+        [var: Symbol, from: Rational, to: Rational, body: Expr, ..rest: Expr],
+        Graphic,
+        |env: &mut Environment, var: &Symbol, from: &Rational, to: &Rational, body: &Span<parser::Operand>, rest: Vec<&Span<parser::Operand>>| {
+            let (from, to) = (from.val.to_f64(), to.val.to_f64());
+            if from >= to {
+                return Err(Error::new(Cause::Invalid {
+                    reason: "the lower bound must be less than the upper bound",
+                })
+                .at_operand(1));
+            }
+
+            env.push_scope();
+            let series = std::iter::once(body)
+                .chain(rest)
+                .enumerate()
+                .map(|(idx, body)| sample(env, var, (from, to), 3 + idx, body))
+                .collect::<Result<Vec<_>, _>>();
+            env.pop_scope();
+
+            Ok(Rc::new(Graphic::new(var.name.clone(), (from, to), series?)))
+        },
+    );
+
+    /// Samples a body expression at evenly spaced points.
+    ///
+    /// The function is left undefined at points where the body cannot be evaluated, but if it
+    /// cannot be evaluated anywhere, the first error is returned instead.
+    fn sample(
+        env: &mut Environment,
+        var: &Symbol,
+        (from, to): (f64, f64),
+        idx: usize,
+        body: &Span<parser::Operand>,
+    ) -> Result<Series, Error> {
+        let mut first_err = None;
+        let mut points = Vec::with_capacity(SAMPLES);
+
+        for i in 0..SAMPLES {
+            let x = from + (to - from) * (i as f64) / ((SAMPLES - 1) as f64);
+            let y = Number::approx(x)
+                .ok_or_else(|| Error::new(Cause::Overflow).at_operand(idx))
+                .and_then(|val| {
+                    env.bind(var.name.clone(), Operand::Rational(Rational { val }));
+                    eval_number(env, idx, body)?.ok_or_else(|| {
+                        Error::new(Cause::Invalid {
+                            reason: "the function must evaluate to a number",
+                        })
+                        .at_operand(idx)
+                    })
+                });
+
+            points.push((x, match y {
+                Ok(it) => Some(it.to_f64()),
+                Err(e) => {
+                    let _ = first_err.get_or_insert(e);
+                    None
+                }
+            }));
+        }
+
+        if let (true, Some(e)) = (points.iter().all(|(_, y)| y.is_none()), first_err) {
+            return Err(e);
+        }
+
+        Ok(Series { label: body.inner.to_string(), points })
+    }
 }
@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! An embeddable IMPL interpreter.
+
+use crate::{
+    evaluator::{self, env::AngleMode, operation::Operation, Environment},
+    span::Span,
+    Callbacks,
+    Error,
+};
+
+/// An IMPL interpreter with its own environment and set of operations.
+///
+/// Interpreters are configured with a [`Builder`], which may register custom operations and
+/// override or remove built-in ones:
+///
+/// ```
+/// use imp_backend::{evaluator::{operation::Operation, Output}, num::Number, Callbacks, Interpreter};
+///
+/// let mut interp = Interpreter::builder()
+///     .operation("double", Operation::from_fn(|a: Number| {
+///         Ok(a.checked_add(&a).unwrap_or(a))
+///     }))
+///     .without_operation("sin")
+///     .build();
+///
+/// let output = interp.process("double 21", Callbacks::default()).unwrap();
+/// assert!(matches!(output.as_slice(), [Output::Text(it)] if it == "42"));
+/// assert!(interp.process("sin 0", Callbacks::default()).is_err());
+/// ```
+#[derive(Default)]
+pub struct Interpreter {
+    env: Environment,
+}
+
+impl Interpreter {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Evaluates one or more lines of IMPL code.
+    ///
+    /// See [`process`](crate::process).
+    pub fn process(&mut self, impl_code: &str, cb: Callbacks) -> Result<Vec<evaluator::Output>, Span<Error>> {
+        crate::process(impl_code, &mut self.env, cb)
+    }
+
+    pub fn env(&self) -> &Environment {
+        &self.env
+    }
+
+    pub fn env_mut(&mut self) -> &mut Environment {
+        &mut self.env
+    }
+}
+
+/// A builder for an [`Interpreter`].
+#[derive(Default)]
+pub struct Builder {
+    env: Environment,
+}
+
+impl Builder {
+    /// Registers an operation under the given name, overriding any built-in operation of the same
+    /// name.
+    pub fn operation(mut self, name: impl Into<String>, operation: Operation) -> Self {
+        let _ = self.env.register_operation(name, operation);
+
+        self
+    }
+
+    /// Removes the operation of the given name, if any.
+    pub fn without_operation(mut self, name: &str) -> Self {
+        let _ = self.env.unregister_operation(name);
+
+        self
+    }
+
+    /// Sets the maximum depth of nested function calls, which is clamped to
+    /// [`MAX_CALL_DEPTH`](crate::evaluator::env::MAX_CALL_DEPTH).
+    pub fn max_call_depth(mut self, it: usize) -> Self {
+        self.env.set_max_call_depth(it);
+
+        self
+    }
+
+    /// Sets the unit in which trigonometric operations measure angles.
+    pub fn angle_mode(mut self, it: AngleMode) -> Self {
+        self.env.set_angle_mode(it);
+
+        self
+    }
+
+    pub fn build(self) -> Interpreter {
+        Interpreter { env: self.env }
+    }
+}
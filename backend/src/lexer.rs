@@ -22,6 +22,13 @@ pub fn lex(impl_code: &str) -> Result<Output, Span<Error>> {
 
     // Iterate over each character/index pair.
     while let Some(first) = idxed_chars.next() {
+        // A minus sign in operation position, as in `(- 3 2)` or `-3 2`, is always the subtraction
+        // operator and never the sign of a negative number.
+        if (first.val == '-') && output.tokens.last().is_none_or(|it| it.inner == Token::LParen) {
+            output.tokens.push(Span::new(Token::Symbol("sub".to_string()), (first.idx)..(first.idx + 1)));
+            continue;
+        }
+
         // Try to create a lexical token given the first character of the token's source code.
         let token = tokenize(&mut idxed_chars, first.val)
             .map(|tkzed| {
@@ -138,16 +145,31 @@ fn tokenize_multi(
     }
 }
 
+/// Operators that are spelled with a single character and the names of the operations they stand
+/// for.
+pub const ALIASES: &[(char, &str)] = &[
+    ('+', "add"),
+    ('-', "sub"),
+    ('*', "mul"),
+    ('/', "div"),
+    ('<', "lt"),
+    ('>', "gt"),
+    ('=', "eq"),
+];
+
 fn tokenize_single(ch: char) -> Option<Token> {
     match ch {
         '(' => Some(Token::LParen),
         ')' => Some(Token::RParen),
-        '+' => Some(Token::Symbol("add".to_string())),
-        '-' => Some(Token::Symbol("sub".to_string())),
-        '*' => Some(Token::Symbol("add".to_string())),
-        '/' => Some(Token::Symbol("div".to_string())),
+        '{' => Some(Token::LBrace),
+        '}' => Some(Token::RBrace),
         '^' => Some(Token::Caret),
-        _ => None,
+        '$' => Some(Token::Dollar),
+        // A minus sign never reaches this point, as the rational number tokenizer accepts it.
+        _ => ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == ch)
+            .map(|(_, name)| Token::Symbol(name.to_string())),
     }
 }
 
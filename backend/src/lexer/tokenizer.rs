@@ -25,13 +25,21 @@ pub const COMMENT: Tokenizer = Tokenizer {
 
 /// A tokenizer that accepts rational numbers.
 pub const RATIONAL: Tokenizer = Tokenizer {
-    accepts: |_, next| {
+    accepts: |current, next| {
         // A rational number may include a digit or decimal point in any location, including the
-        // first and last character.
+        // first and last character. A minus sign may only be the first character.
         // TODO: Make decimal point configurable to a comma.
-        next.is_ascii_digit() || (next == '.')
+        next.is_ascii_digit() || (next == '.') || (current.is_empty() && (next == '-'))
+    },
+    tokenize: |raw| {
+        // A minus sign that is not immediately followed by a number, as in `(sub -x 1)`, is the
+        // subtraction operator; the parser treats it as negation if it is attached to an operand.
+        if raw == "-" {
+            Some(Token::Symbol("sub".to_string()))
+        } else {
+            Some(Token::Rational(raw))
+        }
     },
-    tokenize: |raw| Some(Token::Rational(raw)),
 };
 
 /// A tokenizer that accepts string literals.
@@ -60,8 +68,9 @@ pub const STR_LIT: Tokenizer = Tokenizer {
 
 /// A tokenizer that accepts symbols.
 pub const SYMBOL: Tokenizer = Tokenizer {
-    accepts: |_, next| {
-        next.is_ascii_alphabetic()
+    accepts: |current, next| {
+        // Digits may follow the first character, as in `atan2`.
+        next.is_ascii_alphabetic() || (!current.is_empty() && next.is_ascii_digit())
     },
     tokenize: |raw| {
         Some(Token::Symbol(raw))
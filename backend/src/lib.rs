@@ -8,12 +8,78 @@
 //! This crate is the IMP backend, which provides a [`process`] function that evaluates one or more
 //! lines of IMPL code.
 //!
+//! Applications that embed IMP may instead build an [`Interpreter`], which can be extended with
+//! operations implemented in Rust.
+//!
 //! # IMPL
 //!
 //! IMPL uses S-expressions for function application and LaTeX symbols for function and constant
 //! names. Outer parentheses are optional, and comments are prefixed with a semicolon and terminated
 //! by a line feed.
 //!
+//! Values may be bound to names with `let`, as in `(let x 5)`, and later referred to by name, as in
+//! `(* x 2)`. Bindings live in an [`evaluator::Environment`] that persists across calls to
+//! [`process`].
+//!
+//! Each top-level expression's value is recorded in the environment. `$` refers to the last
+//! result and `$n` to the *n*th result since the environment was created, as in `(* $ 2)` or
+//! `(+ $1 $2)`; negative numbers count backwards, so `$-2` is the second-to-last result.
+//!
+//! A minus sign immediately followed by a number is part of that number, as in `(* 2 -3)`, and
+//! immediately followed by any other operand negates it, so `-x` is sugar for `(sub x)`. In
+//! operation position, as in `(- 3 2)` or `(-3 2)`, it is always subtraction, and `sub` with a
+//! single operand negates it.
+//!
+//! Lists are written in braces, as in `{1 2 3}`, and may be nested. Arithmetic operations apply
+//! element-wise to lists, pairing a number with every element, so `(* {1 2} {3 4})` is `{3 8}` and
+//! `(+ {1 2} 1)` is `{2 3}`. `(at list n)` is the *n*th element, counting backwards if *n* is
+//! negative, `(len list)` is the number of elements, and `(concat a b)` joins lists.
+//!
+//! A matrix is a list of rows, as in `{{1 2} {3 4}}`. `det`, `inv`, `transpose`, and `rank` are
+//! what they say, `(matmul a b)` multiplies matrices, and `(linsolve a b)` solves the linear system
+//! `ax = b`, where a vector such as `{1 2}` stands for a column. `lu` decomposes a matrix into
+//! `{p l u}` such that `pa = lu`, `qr` into `{q r}`, and `eigen` finds its eigenvalues. Matrices
+//! of exact numbers produce exact results, except that `qr` involves square roots.
+//!
+//! The constant `i` is the imaginary unit, so `(+ 1 (* 2 i))` is the complex number `1+2i`.
+//! Arithmetic, `sqrt`, `exp`, `ln`, `log`, and trigonometric and hyperbolic operations, including
+//! their inverses, accept complex numbers, and those that have no real result produce complex ones
+//! instead, so `(sqrt -4)` is `2i` and `(arcsin 2)` is complex. Inverse operations take principal
+//! values. `abs`, `arg`, `conj`, `re`, and `im` are what they say; `(polar z)` is `{(abs z) (arg
+//! z)}`, and `(rect r a)` is the complex number with absolute value `r` and argument `a`. Results
+//! with an imaginary part of zero are real.
+//!
+//! Functions may be defined with `defn`, as in `(defn hyp (a b) (sqrt (+ (* a a) (* b b))))`, and
+//! called like built-in operations, which they shadow. A function without parameters is defined
+//! with empty parentheses, as in `(defn two () 2)`, and called as `(two)`. `(if cond then else)`
+//! evaluates only the chosen branch, so functions may recurse up to a configurable depth of at most
+//! [`MAX_CALL_DEPTH`](evaluator::env::MAX_CALL_DEPTH).
+//!
+//! The big operators `sum` and `prod` bind an index variable to each integer in a range and
+//! combine the values of a body expression, as in `(sum k 1 100 (* k k))`. Similarly, `int`
+//! numerically integrates a body expression, as in `(int x 0 inf (exp (- 0 x)))`, to within an
+//! optional tolerance; `interr` estimates the error of the same integral.
+//!
+//! Expressions are values in their own right. `(quote expr)` produces an expression without
+//! evaluating it, `(deriv x expr)` produces the derivative of an expression with respect to `x`,
+//! and `(eval expr)` evaluates an expression value with the current bindings. `(simplify expr)`
+//! collects like terms, folds constants, and cancels common factors, so that
+//! `(simplify (- (* 3 x) x))` is `(mul 2 x)`; derivatives are simplified in the same way.
+//!
+//! `(solve x (= lhs rhs))` solves an equation for `x`. Polynomial equations, and rational ones
+//! such as `(= (/ 1 x) 2)`, are solved exactly, with several solutions collected into a `roots`
+//! expression, as in `(roots -2 2)`; quadratic factors may also have complex solutions, as in
+//! `(roots (mul -1 i) i)`. Such equations of a degree greater than 64 are rejected. Other equations
+//! are solved numerically, starting from an optional guess.
+//!
+//! `(plot x -10 10 (sin x) (cos x))` samples one or more functions of `x` over a range and
+//! produces a [graphic](evaluator::graphic::Graphic) rather than text; rendering it is left to the
+//! frontend.
+//!
+//! Trigonometric operations measure angles in the unit given by the environment's
+//! [angle mode](evaluator::env::AngleMode), which defaults to radians. `deg`, `rad`, and `grad`
+//! convert an angle from their unit to the current one, so `(sin (deg 90))` is 1 in any mode.
+//!
 //! ## Formal Grammar
 //!
 //! The formal grammar of IMPL is notated here in Augmented Backus–Naur form:
@@ -21,10 +87,14 @@
 //! ```abnf
 //! program = *expression
 //! expression = *"(" operation *operand *")"
-//! operation = 1*ALPHA / operator
-//! operator = "+" / "-" / "*" / "/"
-//! operand = expression / number
-//! number = 1*DIGIT
+//! operation = symbol / operator
+//! operator = "+" / "-" / "*" / "/" / "="
+//! operand = expression / number / symbol / result / list / negation
+//! symbol = ALPHA *(ALPHA / DIGIT)
+//! result = "$" [number]
+//! list = "{" *operand "}" / "(" ")"
+//! negation = "-" operand
+//! number = ["-"] *DIGIT ["." *DIGIT]
 //! ```
 //!
 //! # Pipeline
@@ -44,19 +114,32 @@
 pub mod lexer;
 pub mod parser;
 pub mod evaluator;
+pub mod interpreter;
+pub mod num;
 pub mod span;
+pub mod sym;
+
+pub use interpreter::Interpreter;
 
-use rayon::prelude::*;
 use span::Span;
 use std::fmt;
 
 /// Callbacks for [`process`].
+#[derive(Default)]
 pub struct Callbacks {
     pub inspect_lexer_output: Option<fn(&lexer::Output)>,
     pub inspect_parser_output: Option<fn(&parser::Output)>,
 }
 
-pub fn process(impl_code: &str, cb: Callbacks) -> Result<Vec<evaluator::Output>, Span<Error>> {
+/// Evaluates one or more lines of IMPL code.
+///
+/// Expressions are evaluated in order within `env`, so bindings made by one expression are visible
+/// to those that follow it and to subsequent calls with the same environment.
+pub fn process(
+    impl_code: &str,
+    env: &mut evaluator::Environment,
+    cb: Callbacks,
+) -> Result<Vec<evaluator::Output>, Span<Error>> {
     let output = lexer::lex(impl_code).map_err(|e| e.map(Error::Lexer))?;
     if let Some(cb) = cb.inspect_lexer_output {
         cb(&output);
@@ -69,8 +152,8 @@ pub fn process(impl_code: &str, cb: Callbacks) -> Result<Vec<evaluator::Output>,
 
     output
         .ast
-        .into_par_iter()
-        .map(|expr| evaluator::eval_ast(expr.inner).map_err(|e| e.map(Error::Evaluator)))
+        .into_iter()
+        .map(|expr| evaluator::eval_ast(expr, env).map_err(|e| e.map(Error::Evaluator)))
         .collect()
 }
 
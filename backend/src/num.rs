@@ -0,0 +1,283 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The IMPL numeric tower.
+//!
+//! Numbers are exact rationals with arbitrary-precision numerators and denominators for as long as
+//! possible. Operations without exact rational results---such as most transcendental
+//! functions---produce inexact floating-point approximations instead, and any arithmetic involving
+//! an inexact number is itself inexact.
+
+pub mod complex;
+pub mod matrix;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed as _, ToPrimitive as _, Zero as _};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// The maximum number of bits in the numerator or denominator of an exact result before it is
+/// considered to overflow.
+///
+/// Without this limit, innocent-looking expressions such as `(pow 10 1000000000)` would exhaust
+/// memory.
+const MAX_EXACT_BITS: u64 = 1 << 24;
+
+/// A real number.
+#[derive(Clone, Debug)]
+pub enum Number {
+    /// An exact rational number.
+    Exact(BigRational),
+    /// A finite floating-point approximation of a real number.
+    Inexact(f64),
+}
+
+impl Number {
+    pub fn from_integer(it: i64) -> Self {
+        Self::Exact(BigRational::from_integer(BigInt::from(it)))
+    }
+
+    /// Creates an exact fraction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `denom` is zero.
+    pub fn from_ratio(numer: i64, denom: i64) -> Self {
+        Self::Exact(BigRational::new(BigInt::from(numer), BigInt::from(denom)))
+    }
+
+    /// Creates an inexact number, or returns `None` if `val` is not finite.
+    pub fn approx(val: f64) -> Option<Self> {
+        val.is_finite().then_some(Self::Inexact(val))
+    }
+
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Self::Exact(_))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Self::Exact(it) => it.is_zero(),
+            Self::Inexact(it) => *it == 0.0,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Self::Exact(it) => it.is_negative(),
+            Self::Inexact(it) => *it < 0.0,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        match self {
+            Self::Exact(it) => it.is_integer(),
+            Self::Inexact(it) => it.fract() == 0.0,
+        }
+    }
+
+    /// Converts this number to the nearest floating-point value.
+    ///
+    /// The result is infinite, with the sign of this number, if this number is too large in
+    /// magnitude to be represented, and zero if it is too small.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            // The conversion itself rounds to infinity or zero, so this fallback only agrees with it.
+            Self::Exact(it) => it.to_f64().unwrap_or_else(|| {
+                if it.is_negative() {
+                    f64::NEG_INFINITY
+                } else {
+                    f64::INFINITY
+                }
+            }),
+            Self::Inexact(it) => *it,
+        }
+    }
+
+    /// Converts this number to an integer, or returns `None` if it is not an integer or does not fit
+    /// in an `i64`.
+    pub fn to_i64(&self) -> Option<i64> {
+        if !self.is_integer() {
+            return None;
+        }
+
+        match self {
+            Self::Exact(it) => it.to_integer().to_i64(),
+            Self::Inexact(it) => it.to_i64(),
+        }
+    }
+
+    pub fn neg(&self) -> Self {
+        match self {
+            Self::Exact(it) => Self::Exact(-it),
+            Self::Inexact(it) => Self::Inexact(-it),
+        }
+    }
+
+    pub fn abs(&self) -> Self {
+        match self {
+            Self::Exact(it) => Self::Exact(it.abs()),
+            Self::Inexact(it) => Self::Inexact(it.abs()),
+        }
+    }
+
+    /// Returns `None` if the result is inexact and not finite.
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        self.combine(other, |a, b| a + b, |a, b| a + b)
+    }
+
+    /// Returns `None` if the result is inexact and not finite.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.combine(other, |a, b| a - b, |a, b| a - b)
+    }
+
+    /// Returns `None` if the result is inexact and not finite.
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        self.combine(other, |a, b| a * b, |a, b| a * b)
+    }
+
+    /// Returns `None` if `other` is zero or the result is inexact and not finite.
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+
+        self.combine(other, |a, b| a / b, |a, b| a / b)
+    }
+
+    /// Raises this number to an integer power.
+    ///
+    /// Returns `None` if this number is zero and `exp` is negative, or if the result is too large to
+    /// be represented.
+    pub fn checked_powi(&self, exp: i32) -> Option<Self> {
+        if self.is_zero() && exp < 0 {
+            return None;
+        }
+
+        match self {
+            Self::Exact(it) => {
+                let bits = it.numer().bits().max(it.denom().bits());
+                if bits.saturating_mul(u64::from(exp.unsigned_abs())) > MAX_EXACT_BITS {
+                    return None;
+                }
+
+                Some(Self::Exact(it.pow(exp)))
+            }
+            Self::Inexact(it) => Self::approx(it.powi(exp)),
+        }
+    }
+
+    /// Computes the square root of this number, which is exact if this number is the square of an
+    /// exact rational.
+    ///
+    /// Returns `None` if this number is negative.
+    pub fn sqrt(&self) -> Option<Self> {
+        if self.is_negative() {
+            return None;
+        }
+
+        if let Self::Exact(it) = self {
+            let numer = it.numer().sqrt();
+            let denom = it.denom().sqrt();
+            if (&numer * &numer == *it.numer()) && (&denom * &denom == *it.denom()) {
+                return Some(Self::Exact(BigRational::new(numer, denom)));
+            }
+        }
+
+        Self::approx(self.to_f64().sqrt())
+    }
+
+    /// Applies a function to the floating-point approximation of this number.
+    ///
+    /// Returns `None` if the result is not finite.
+    pub fn map_approx(&self, f: impl Fn(f64) -> f64) -> Option<Self> {
+        Self::approx(f(self.to_f64()))
+    }
+
+    fn combine(
+        &self,
+        other: &Self,
+        exact: impl Fn(&BigRational, &BigRational) -> BigRational,
+        inexact: impl Fn(f64, f64) -> f64,
+    ) -> Option<Self> {
+        match (self, other) {
+            (Self::Exact(a), Self::Exact(b)) => Some(Self::Exact(exact(a, b))),
+            // Arithmetic involving an inexact number is inexact.
+            (a, b) => Self::approx(inexact(a.to_f64(), b.to_f64())),
+        }
+    }
+
+    /// Converts this number to an exact rational.
+    ///
+    /// Inexact numbers are converted to the exact value of their floating-point representation.
+    fn to_exact(&self) -> BigRational {
+        match self {
+            Self::Exact(it) => it.clone(),
+            Self::Inexact(it) => BigRational::from_float(*it)
+                // This `expect` is OK because inexact numbers are always finite.
+                .expect("inexact number should be finite"),
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Inexact(a), Self::Inexact(b)) => a.partial_cmp(b),
+            (a, b) => Some(a.to_exact().cmp(&b.to_exact())),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(it) if it.is_integer() => write!(f, "{}", it.numer()),
+            Self::Exact(it) => write!(f, "{}/{}", it.numer(), it.denom()),
+            Self::Inexact(it) => write!(f, "{}", it),
+        }
+    }
+}
+
+/// An error produced when parsing a [`Number`] from a string.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseError;
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid number")
+    }
+}
+
+impl FromStr for Number {
+    type Err = ParseError;
+
+    /// Parses a decimal number, such as `12`, `-1.25`, or `.5`, into an exact rational.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(s) = s.strip_prefix('-') {
+            return Self::from_str(s).map(|it| it.neg());
+        }
+
+        let (int, fract) = s.split_once('.').unwrap_or((s, ""));
+        if int.is_empty() && fract.is_empty() {
+            return Err(ParseError);
+        }
+        if !int.chars().chain(fract.chars()).all(|it| it.is_ascii_digit()) {
+            return Err(ParseError);
+        }
+
+        // The digits of the number, without the decimal point, form the numerator; the denominator
+        // is the power of ten that restores the decimal point.
+        let numer: BigInt = format!("0{}{}", int, fract).parse().map_err(|_| ParseError)?;
+        let denom = num_traits::pow(BigInt::from(10), fract.len());
+
+        Ok(Self::Exact(BigRational::new(numer, denom)))
+    }
+}
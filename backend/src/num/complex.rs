@@ -0,0 +1,327 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Complex numbers.
+//!
+//! The real and imaginary parts are [numbers](Number), so arithmetic on complex numbers with exact
+//! parts is exact. Transcendental functions are computed from floating-point approximations.
+
+use super::{Number, MAX_EXACT_BITS};
+use std::fmt;
+
+/// A complex number.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Complex {
+    pub re: Number,
+    pub im: Number,
+}
+
+impl Complex {
+    pub fn new(re: Number, im: Number) -> Self {
+        Self { re, im }
+    }
+
+    /// The imaginary unit.
+    pub fn i() -> Self {
+        Self::new(Number::from_integer(0), Number::from_integer(1))
+    }
+
+    pub fn from_real(re: Number) -> Self {
+        Self::new(re, Number::from_integer(0))
+    }
+
+    /// Creates an inexact complex number, or returns `None` if either part is not finite.
+    pub fn approx(re: f64, im: f64) -> Option<Self> {
+        Some(Self::new(Number::approx(re)?, Number::approx(im)?))
+    }
+
+    /// Creates a complex number from its absolute value and its argument in radians.
+    ///
+    /// Returns `None` if the result is not finite.
+    pub fn from_polar(abs: f64, arg: f64) -> Option<Self> {
+        Self::approx(abs * arg.cos(), abs * arg.sin())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.re.is_zero() && self.im.is_zero()
+    }
+
+    pub fn is_real(&self) -> bool {
+        self.im.is_zero()
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::new(self.re.neg(), self.im.neg())
+    }
+
+    /// The complex conjugate.
+    pub fn conj(&self) -> Self {
+        Self::new(self.re.clone(), self.im.neg())
+    }
+
+    /// Returns `None` if the result is inexact and not finite.
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(Self::new(self.re.checked_add(&other.re)?, self.im.checked_add(&other.im)?))
+    }
+
+    /// Returns `None` if the result is inexact and not finite.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(Self::new(self.re.checked_sub(&other.re)?, self.im.checked_sub(&other.im)?))
+    }
+
+    /// Returns `None` if the result is inexact and not finite.
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let re = self.re.checked_mul(&other.re)?.checked_sub(&self.im.checked_mul(&other.im)?)?;
+        let im = self.re.checked_mul(&other.im)?.checked_add(&self.im.checked_mul(&other.re)?)?;
+
+        Some(Self::new(re, im))
+    }
+
+    /// Returns `None` if `other` is zero or the result is inexact and not finite.
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        // Multiply the numerator and denominator by the conjugate of the denominator, which makes
+        // the denominator real.
+        let denom = other.abs_sq()?;
+        let numer = self.checked_mul(&other.conj())?;
+
+        Some(Self::new(numer.re.checked_div(&denom)?, numer.im.checked_div(&denom)?))
+    }
+
+    /// Raises this number to an integer power.
+    ///
+    /// Returns `None` if this number is zero and `exp` is negative, or if the result is too large to
+    /// be represented.
+    pub fn checked_powi(&self, exp: i32) -> Option<Self> {
+        if exp < 0 {
+            let one = Self::from_real(Number::from_integer(1));
+            return one.checked_div(&self.checked_powi(exp.checked_neg()?)?);
+        }
+
+        let bits = [&self.re, &self.im]
+            .into_iter()
+            .map(|it| match it {
+                Number::Exact(it) => it.numer().bits().max(it.denom().bits()),
+                Number::Inexact(_) => 0,
+            })
+            .max()
+            .unwrap_or(0);
+        // Each multiplication may also carry into one more bit.
+        if (bits + 1).saturating_mul(u64::from(exp.unsigned_abs())) > MAX_EXACT_BITS {
+            return None;
+        }
+
+        // Exponentiation by squaring.
+        let mut result = Self::from_real(Number::from_integer(1));
+        let mut base = self.clone();
+        let mut exp = exp;
+        while exp > 0 {
+            if exp % 2 == 1 {
+                result = result.checked_mul(&base)?;
+            }
+            exp /= 2;
+            if exp > 0 {
+                base = base.checked_mul(&base)?;
+            }
+        }
+
+        Some(result)
+    }
+
+    /// Raises this number to a complex power, taking the principal value.
+    ///
+    /// Returns `None` if this number is zero or the result is not finite.
+    pub fn powc(&self, exp: &Self) -> Option<Self> {
+        self.ln()?.checked_mul(exp)?.exp()
+    }
+
+    /// The square of the absolute value, which is exact if both parts are.
+    fn abs_sq(&self) -> Option<Number> {
+        self.re.checked_mul(&self.re)?.checked_add(&self.im.checked_mul(&self.im)?)
+    }
+
+    /// The absolute value, which is exact if it is rational.
+    ///
+    /// Returns `None` on overflow.
+    pub fn abs(&self) -> Option<Number> {
+        if self.re.is_exact() && self.im.is_exact() {
+            self.abs_sq()?.sqrt()
+        } else {
+            // Squaring the parts could overflow needlessly.
+            Number::approx(self.re.to_f64().hypot(self.im.to_f64()))
+        }
+    }
+
+    /// The argument in radians, between -π and π.
+    pub fn arg(&self) -> f64 {
+        self.im.to_f64().atan2(self.re.to_f64())
+    }
+
+    /// The principal square root, which is exact if it has rational parts.
+    ///
+    /// Returns `None` on overflow.
+    pub fn sqrt(&self) -> Option<Self> {
+        let abs = self.abs()?;
+        let two = Number::from_integer(2);
+
+        // The square root of `a + bi` is `sqrt((|z| + a) / 2) ± i sqrt((|z| - a) / 2)`, with the
+        // sign of `b`.
+        let part = |it: Number| {
+            let it = it.checked_div(&two)?;
+            if it.is_negative() {
+                // Rounding can make an inexact part slightly negative when it should be zero.
+                Some(Number::from_integer(0))
+            } else {
+                it.sqrt()
+            }
+        };
+        let re = part(abs.checked_add(&self.re)?)?;
+        let im = part(abs.checked_sub(&self.re)?)?;
+
+        Some(Self::new(re, if self.im.is_negative() { im.neg() } else { im }))
+    }
+
+    /// Returns `None` if the result is not finite.
+    pub fn exp(&self) -> Option<Self> {
+        Self::from_polar(self.re.to_f64().exp(), self.im.to_f64())
+    }
+
+    /// The principal natural logarithm.
+    ///
+    /// Returns `None` if this number is zero or the result is not finite.
+    pub fn ln(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+
+        Self::approx(self.abs()?.to_f64().ln(), self.arg())
+    }
+
+    /// The sine of an angle in radians.
+    ///
+    /// Returns `None` if the result is not finite.
+    pub fn sin(&self) -> Option<Self> {
+        let (re, im) = (self.re.to_f64(), self.im.to_f64());
+
+        Self::approx(re.sin() * im.cosh(), re.cos() * im.sinh())
+    }
+
+    /// The cosine of an angle in radians.
+    ///
+    /// Returns `None` if the result is not finite.
+    pub fn cos(&self) -> Option<Self> {
+        let (re, im) = (self.re.to_f64(), self.im.to_f64());
+
+        Self::approx(re.cos() * im.cosh(), -re.sin() * im.sinh())
+    }
+
+    /// The tangent of an angle in radians.
+    ///
+    /// Returns `None` if the cosine is zero or the result is not finite.
+    pub fn tan(&self) -> Option<Self> {
+        self.sin()?.checked_div(&self.cos()?)
+    }
+
+    /// The principal arcsine, in radians.
+    ///
+    /// Returns `None` if the result is not finite.
+    pub fn asin(&self) -> Option<Self> {
+        // asin z = -i ln(iz + sqrt(1 - z²))
+        let one = Self::from_real(Number::from_integer(1));
+        let root = one.checked_sub(&self.checked_mul(self)?)?.sqrt()?;
+
+        Self::i().checked_mul(self)?.checked_add(&root)?.ln()?.checked_mul(&Self::i().neg())
+    }
+
+    /// The principal arccosine, in radians.
+    ///
+    /// Returns `None` if the result is not finite.
+    pub fn acos(&self) -> Option<Self> {
+        // acos z = π/2 - asin z
+        Self::approx(std::f64::consts::FRAC_PI_2, 0.)?.checked_sub(&self.asin()?)
+    }
+
+    /// The principal arctangent, in radians.
+    ///
+    /// Returns `None` if this number is `i` or `-i`, or if the result is not finite.
+    pub fn atan(&self) -> Option<Self> {
+        // atan z = i/2 (ln(1 - iz) - ln(1 + iz))
+        let one = Self::from_real(Number::from_integer(1));
+        let iz = Self::i().checked_mul(self)?;
+        let diff = one.checked_sub(&iz)?.ln()?.checked_sub(&one.checked_add(&iz)?.ln()?)?;
+
+        diff.checked_mul(&Self::new(Number::from_integer(0), Number::from_ratio(1, 2)))
+    }
+
+    /// Returns `None` if the result is not finite.
+    pub fn sinh(&self) -> Option<Self> {
+        let (re, im) = (self.re.to_f64(), self.im.to_f64());
+
+        Self::approx(re.sinh() * im.cos(), re.cosh() * im.sin())
+    }
+
+    /// Returns `None` if the result is not finite.
+    pub fn cosh(&self) -> Option<Self> {
+        let (re, im) = (self.re.to_f64(), self.im.to_f64());
+
+        Self::approx(re.cosh() * im.cos(), re.sinh() * im.sin())
+    }
+
+    /// Returns `None` if the hyperbolic cosine is zero or the result is not finite.
+    pub fn tanh(&self) -> Option<Self> {
+        self.sinh()?.checked_div(&self.cosh()?)
+    }
+
+    /// The principal inverse hyperbolic sine.
+    ///
+    /// Returns `None` if the result is not finite.
+    pub fn asinh(&self) -> Option<Self> {
+        // asinh z = ln(z + sqrt(z² + 1))
+        let one = Self::from_real(Number::from_integer(1));
+        let root = self.checked_mul(self)?.checked_add(&one)?.sqrt()?;
+
+        self.checked_add(&root)?.ln()
+    }
+
+    /// The principal inverse hyperbolic cosine.
+    ///
+    /// Returns `None` if the result is not finite.
+    pub fn acosh(&self) -> Option<Self> {
+        // acosh z = ln(z + sqrt(z + 1) sqrt(z - 1))
+        let one = Self::from_real(Number::from_integer(1));
+        let root = self.checked_add(&one)?.sqrt()?.checked_mul(&self.checked_sub(&one)?.sqrt()?)?;
+
+        self.checked_add(&root)?.ln()
+    }
+
+    /// The principal inverse hyperbolic tangent.
+    ///
+    /// Returns `None` if this number is 1 or -1, or if the result is not finite.
+    pub fn atanh(&self) -> Option<Self> {
+        // atanh z = ln((1 + z) / (1 - z)) / 2
+        let one = Self::from_real(Number::from_integer(1));
+        let ratio = one.checked_add(self)?.checked_div(&one.checked_sub(self)?)?;
+
+        ratio.ln()?.checked_mul(&Self::from_real(Number::from_ratio(1, 2)))
+    }
+}
+
+impl fmt::Display for Complex {
+    /// Writes this number as `a+bi`, omitting a real part of zero and an imaginary coefficient of
+    /// one.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.re.is_zero() {
+            write!(f, "{}", self.re)?;
+            write!(f, "{}", if self.im.is_negative() { "-" } else { "+" })?;
+        } else if self.im.is_negative() {
+            write!(f, "-")?;
+        }
+
+        let im = self.im.abs();
+        if im != Number::from_integer(1) {
+            write!(f, "{}", im)?;
+        }
+
+        write!(f, "i")
+    }
+}
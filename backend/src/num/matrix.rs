@@ -0,0 +1,377 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Matrices of numbers and linear algebra.
+//!
+//! Algorithms are based on Gaussian elimination, which only divides by pivots, so matrices of exact
+//! numbers produce exact results. The exception is QR decomposition, which normalizes columns and
+//! is exact only when their lengths are rational.
+
+use super::Number;
+use std::fmt;
+
+/// A dense matrix of numbers, stored in row-major order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    entries: Vec<Number>,
+}
+
+/// An error produced by a matrix operation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The result is too large to be represented.
+    Overflow,
+    /// The matrix has no inverse.
+    Singular,
+    /// The columns of the matrix are linearly dependent.
+    Dependent,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "overflow"),
+            Self::Singular => write!(f, "the matrix is singular"),
+            Self::Dependent => write!(f, "the columns of the matrix are linearly dependent"),
+        }
+    }
+}
+
+/// The decomposition `PA = LU` of a square matrix `A`, where `P` is a permutation matrix, `L` is
+/// lower triangular with ones on its diagonal, and `U` is upper triangular.
+#[derive(Clone, Debug)]
+pub struct Lu {
+    pub p: Matrix,
+    pub l: Matrix,
+    pub u: Matrix,
+}
+
+/// The decomposition `A = QR` of a matrix `A` with linearly independent columns, where `Q` has
+/// orthonormal columns and `R` is upper triangular.
+#[derive(Clone, Debug)]
+pub struct Qr {
+    pub q: Matrix,
+    pub r: Matrix,
+}
+
+impl Matrix {
+    /// Creates a matrix from its entries in row-major order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of entries is not `rows * cols`.
+    pub fn new(rows: usize, cols: usize, entries: Vec<Number>) -> Self {
+        assert_eq!(entries.len(), rows * cols, "matrix should have `rows * cols` entries");
+
+        Self { rows, cols, entries }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut it = Self::zero(size, size);
+        for idx in 0..size {
+            it[(idx, idx)] = Number::from_integer(1);
+        }
+
+        it
+    }
+
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        Self::new(rows, cols, vec![Number::from_integer(0); rows * cols])
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Iterates over the rows of this matrix, each a slice of entries.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[Number]> {
+        // `max(1)` because chunks must not be empty; a matrix without columns has no entries.
+        self.entries.chunks(self.cols.max(1)).take(self.rows)
+    }
+
+    pub fn transpose(&self) -> Self {
+        let entries = (0..self.cols)
+            .flat_map(|col| (0..self.rows).map(move |row| (row, col)))
+            .map(|idx| self[idx].clone())
+            .collect();
+
+        Self::new(self.cols, self.rows, entries)
+    }
+
+    /// Multiplies this matrix by another, which must have as many rows as this matrix has columns.
+    pub fn checked_mul(&self, other: &Self) -> Result<Self, Error> {
+        debug_assert_eq!(self.cols, other.rows);
+
+        let mut entries = Vec::with_capacity(self.rows * other.cols);
+        for row in 0..self.rows {
+            for col in 0..other.cols {
+                let entry = (0..self.cols).try_fold(Number::from_integer(0), |acc, idx| {
+                    acc.checked_add(&self[(row, idx)].checked_mul(&other[(idx, col)])?)
+                });
+                entries.push(entry.ok_or(Error::Overflow)?);
+            }
+        }
+
+        Ok(Self::new(self.rows, other.cols, entries))
+    }
+
+    /// Reduces this matrix to row echelon form in place, returning the columns of the pivots and
+    /// whether an odd number of rows were swapped.
+    ///
+    /// Each row operation is also applied to `with`, which must have as many rows as this matrix.
+    fn eliminate(&mut self, with: &mut Self) -> Result<(Vec<usize>, bool), Error> {
+        let mut pivots = Vec::new();
+        let mut odd = false;
+
+        for col in 0..self.cols {
+            let row = pivots.len();
+            if row == self.rows {
+                break;
+            }
+
+            // The largest entry is chosen as the pivot to limit rounding of inexact numbers; any
+            // nonzero entry would do for exact ones.
+            let pivot = (row..self.rows)
+                .filter(|it| !self[(*it, col)].is_zero())
+                .max_by(|a, b| self[(*a, col)].to_f64().abs().total_cmp(&self[(*b, col)].to_f64().abs()));
+            let Some(pivot) = pivot else {
+                continue;
+            };
+            if pivot != row {
+                self.swap_rows(row, pivot);
+                with.swap_rows(row, pivot);
+                odd = !odd;
+            }
+
+            for below in (row + 1)..self.rows {
+                let factor = self[(below, col)].checked_div(&self[(row, col)]).ok_or(Error::Overflow)?;
+                self.sub_row(below, row, &factor)?;
+                with.sub_row(below, row, &factor)?;
+            }
+            pivots.push(col);
+        }
+
+        Ok((pivots, odd))
+    }
+
+    /// Continues the elimination of a matrix in row echelon form to reduced row echelon form.
+    fn reduce(&mut self, with: &mut Self, pivots: &[usize]) -> Result<(), Error> {
+        for (row, col) in pivots.iter().copied().enumerate().rev() {
+            let pivot = self[(row, col)].clone();
+            self.scale_row(row, &pivot)?;
+            with.scale_row(row, &pivot)?;
+
+            for above in 0..row {
+                let factor = self[(above, col)].clone();
+                self.sub_row(above, row, &factor)?;
+                with.sub_row(above, row, &factor)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for col in 0..self.cols {
+            self.entries.swap(a * self.cols + col, b * self.cols + col);
+        }
+    }
+
+    /// Subtracts `factor` times row `src` from row `dst`.
+    fn sub_row(&mut self, dst: usize, src: usize, factor: &Number) -> Result<(), Error> {
+        for col in 0..self.cols {
+            let entry = self[(dst, col)]
+                .checked_sub(&factor.checked_mul(&self[(src, col)]).ok_or(Error::Overflow)?)
+                .ok_or(Error::Overflow)?;
+            self[(dst, col)] = entry;
+        }
+
+        Ok(())
+    }
+
+    /// Divides row `row` by `divisor`.
+    fn scale_row(&mut self, row: usize, divisor: &Number) -> Result<(), Error> {
+        for col in 0..self.cols {
+            self[(row, col)] = self[(row, col)].checked_div(divisor).ok_or(Error::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn rank(&self) -> Result<usize, Error> {
+        let mut it = self.clone();
+        let (pivots, _) = it.eliminate(&mut Self::zero(self.rows, 0))?;
+
+        Ok(pivots.len())
+    }
+
+    /// Computes the determinant of this matrix, which must be square.
+    pub fn det(&self) -> Result<Number, Error> {
+        debug_assert!(self.is_square());
+
+        let mut it = self.clone();
+        let (pivots, odd) = it.eliminate(&mut Self::zero(self.rows, 0))?;
+        if pivots.len() < self.rows {
+            return Ok(Number::from_integer(0));
+        }
+
+        let init = Number::from_integer(if odd { -1 } else { 1 });
+        (0..self.rows)
+            .try_fold(init, |acc, idx| acc.checked_mul(&it[(idx, idx)]))
+            .ok_or(Error::Overflow)
+    }
+
+    /// Computes the inverse of this matrix, which must be square.
+    pub fn inverse(&self) -> Result<Self, Error> {
+        self.solve(&Self::identity(self.rows))
+    }
+
+    /// Solves `AX = B` for `X`, where `A` is this matrix, which must be square, and `B` has as many
+    /// rows as `A`.
+    pub fn solve(&self, b: &Self) -> Result<Self, Error> {
+        debug_assert!(self.is_square() && (self.rows == b.rows));
+
+        let mut a = self.clone();
+        let mut x = b.clone();
+        let (pivots, _) = a.eliminate(&mut x)?;
+        if pivots.len() < self.rows {
+            return Err(Error::Singular);
+        }
+        a.reduce(&mut x, &pivots)?;
+
+        Ok(x)
+    }
+
+    /// Decomposes this matrix, which must be square, with partial pivoting.
+    pub fn lu(&self) -> Result<Lu, Error> {
+        debug_assert!(self.is_square());
+
+        let size = self.rows;
+        let mut u = self.clone();
+        let mut p = Self::identity(size);
+        let mut l = Self::zero(size, size);
+
+        // This is `eliminate`, except that the multipliers are recorded in `L`, whose rows are
+        // swapped along with those of `U`.
+        for col in 0..size {
+            let pivot = (col..size)
+                .filter(|it| !u[(*it, col)].is_zero())
+                .max_by(|a, b| u[(*a, col)].to_f64().abs().total_cmp(&u[(*b, col)].to_f64().abs()));
+            let Some(pivot) = pivot else {
+                continue;
+            };
+            if pivot != col {
+                u.swap_rows(col, pivot);
+                p.swap_rows(col, pivot);
+                l.swap_rows(col, pivot);
+            }
+
+            for below in (col + 1)..size {
+                let factor = u[(below, col)].checked_div(&u[(col, col)]).ok_or(Error::Overflow)?;
+                u.sub_row(below, col, &factor)?;
+                l[(below, col)] = factor;
+            }
+        }
+        for idx in 0..size {
+            l[(idx, idx)] = Number::from_integer(1);
+        }
+
+        Ok(Lu { p, l, u })
+    }
+
+    /// Decomposes this matrix with the Gram–Schmidt process.
+    pub fn qr(&self) -> Result<Qr, Error> {
+        let mut q = self.clone();
+        let mut r = Self::zero(self.cols, self.cols);
+
+        for col in 0..self.cols {
+            // Subtract the projections onto the previous columns, which are already orthonormal.
+            for prev in 0..col {
+                let dot = q.dot_cols(prev, col)?;
+                for row in 0..self.rows {
+                    let entry = q[(row, col)]
+                        .checked_sub(&dot.checked_mul(&q[(row, prev)]).ok_or(Error::Overflow)?)
+                        .ok_or(Error::Overflow)?;
+                    q[(row, col)] = entry;
+                }
+                r[(prev, col)] = dot;
+            }
+
+            let norm = q.dot_cols(col, col)?.sqrt().ok_or(Error::Overflow)?;
+            if norm.is_zero() {
+                return Err(Error::Dependent);
+            }
+            for row in 0..self.rows {
+                q[(row, col)] = q[(row, col)].checked_div(&norm).ok_or(Error::Overflow)?;
+            }
+            r[(col, col)] = norm;
+        }
+
+        Ok(Qr { q, r })
+    }
+
+    fn dot_cols(&self, a: usize, b: usize) -> Result<Number, Error> {
+        (0..self.rows)
+            .try_fold(Number::from_integer(0), |acc, row| {
+                acc.checked_add(&self[(row, a)].checked_mul(&self[(row, b)])?)
+            })
+            .ok_or(Error::Overflow)
+    }
+
+    /// Computes the characteristic polynomial `det(xI - A)` of this matrix, which must be square,
+    /// as coefficients in ascending order of degree.
+    ///
+    /// The Faddeev–LeVerrier algorithm is used, which only divides by integers.
+    pub fn char_poly(&self) -> Result<Vec<Number>, Error> {
+        debug_assert!(self.is_square());
+
+        let size = self.rows;
+        let mut coefs = vec![Number::from_integer(0); size + 1];
+        coefs[size] = Number::from_integer(1);
+
+        // `am` is `A M_{k-1}`, where `M_k = A M_{k-1} + c_{n-k+1} I` and `M_0 = 0`.
+        let mut am = Self::zero(size, size);
+        for k in 1..=size {
+            let mut m = am;
+            for idx in 0..size {
+                m[(idx, idx)] = m[(idx, idx)].checked_add(&coefs[size - k + 1]).ok_or(Error::Overflow)?;
+            }
+            am = self.checked_mul(&m)?;
+
+            let trace = (0..size)
+                .try_fold(Number::from_integer(0), |acc, idx| acc.checked_add(&am[(idx, idx)]))
+                .ok_or(Error::Overflow)?;
+            let divisor = i64::try_from(k).map_err(|_| Error::Overflow)?;
+            coefs[size - k] = trace.neg().checked_div(&Number::from_integer(divisor)).ok_or(Error::Overflow)?;
+        }
+
+        Ok(coefs)
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for Matrix {
+    type Output = Number;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Number {
+        &self.entries[row * self.cols + col]
+    }
+}
+
+impl std::ops::IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Number {
+        &mut self.entries[row * self.cols + col]
+    }
+}
@@ -12,9 +12,9 @@ pub use err::Error;
 
 use crate::{
     lexer::{self, Token},
+    num::Number,
     span::Span,
 };
-use rust_decimal::Decimal;
 use std::fmt;
 use tokens::Tokens;
 
@@ -30,7 +30,7 @@ pub fn parse(input: lexer::Output) -> Result<Output, Span<Error>> {
 }
 
 /// An S-expression.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Expr {
     pub operation: Span<Operation>,
     pub operands: Vec<Span<Operand>>,
@@ -38,16 +38,12 @@ pub struct Expr {
 
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "({} {})",
-            self.operation.inner,
-            self.operands
-                .iter()
-                .map(|operand| operand.inner.to_string())
-                .collect::<Vec<String>>()
-                .join(" ")
-        )
+        write!(f, "({}", self.operation.inner)?;
+        for operand in self.operands.iter() {
+            write!(f, " {}", operand.inner)?;
+        }
+
+        write!(f, ")")
     }
 }
 
@@ -56,6 +52,33 @@ impl Expr {
         // S-expressions begin with a left parenthesis: '('.
         let l_paren = tokens.expect(err::Subject::Token(Some(Token::LParen)), |token| token.inner == Token::LParen)?;
 
+        // A previous result alone, as in `($3)`, evaluates to that result just as a bound name does.
+        if let Some(Token::Dollar) = tokens.peek().map(|it| it.inner) {
+            let result = Self::parse_recall(tokens)?;
+            let r_paren = tokens.expect(err::Subject::Token(Some(Token::RParen)), |token| {
+                token.inner == Token::RParen
+            })?;
+
+            return Ok(Span::new(result.inner, (l_paren.range.start)..(r_paren.range.end)));
+        }
+
+        // Likewise, a list alone, as in `({1 2 3})`, evaluates to itself. This is sugar for
+        // `(eval {1 2 3})`.
+        if let Some(Token::LBrace) = tokens.peek().map(|it| it.inner) {
+            let list = List::parse(tokens)?;
+            let r_paren = tokens.expect(err::Subject::Token(Some(Token::RParen)), |token| {
+                token.inner == Token::RParen
+            })?;
+
+            return Ok(Span::new(
+                Self {
+                    operation: Span::new(Operation { name: "eval".to_string() }, list.range.start..list.range.start),
+                    operands: vec![list.map(Operand::List)],
+                },
+                (l_paren.range.start)..(r_paren.range.end),
+            ));
+        }
+
         let operation = Operation::parse(tokens)?;
         let mut operands = Vec::new();
 
@@ -93,8 +116,8 @@ impl Expr {
                         0..end,
                     ),
                     operands: vec![
-                        exp,
                         Span::new(Operand::Expr(result.inner), result.range),
+                        exp,
                     ],
                 },
                 (l_paren.range.start)..end,
@@ -103,6 +126,37 @@ impl Expr {
             Ok(result)
         }
     }
+
+    /// Parses a reference to a previous result: `$` for the last result or `$n` for the *n*th
+    /// result, counting backwards from the last if *n* is negative.
+    ///
+    /// This is sugar for `(ans -1)` or `(ans n)`.
+    fn parse_recall(tokens: &mut Tokens) -> Result<Span<Self>, Span<Error>> {
+        // This `expect` is OK because the caller has peeked a dollar sign.
+        let dollar = tokens.next().expect("dollar sign should be next");
+
+        // The index must immediately follow the dollar sign; `$ 3` is the last result followed by 3.
+        let idx = match tokens.peek() {
+            Some(Span { inner: Token::Rational(val), range }) if range.start == dollar.range.end => {
+                tokens.advance();
+                let val = val
+                    .parse()
+                    .map_err(|_| Span::new(Error::invalid(err::Subject::Rational), range.clone()))?;
+
+                Span::new(Rational { val }, range)
+            }
+            _ => Span::new(Rational { val: Number::from_integer(-1) }, dollar.range.clone()),
+        };
+        let range = (dollar.range.start)..(idx.range.end);
+
+        Ok(Span::new(
+            Self {
+                operation: Span::new(Operation { name: "ans".to_string() }, dollar.range),
+                operands: vec![idx.map(Operand::Rational)],
+            },
+            range,
+        ))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -134,9 +188,10 @@ impl Operation {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Operand {
     Expr(Expr),
+    List(List),
     Rational(Rational),
     StrLit(StrLit),
     Symbol(Symbol),
@@ -146,6 +201,7 @@ impl fmt::Display for Operand {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Expr(it) => it.fmt(f),
+            Self::List(it) => it.fmt(f),
             Self::Rational(it) => it.fmt(f),
             Self::StrLit(it) => it.fmt(f),
             Self::Symbol(it) => it.fmt(f),
@@ -160,6 +216,17 @@ impl Operand {
             .ok_or_else(|| tokens.expected(err::Subject::Operand))?;
 
         if let Token::LParen = determinant.inner {
+            // Empty parentheses, as in the parameters of `(defn f () 1)`, are an empty list.
+            if let Some(r_paren @ Span { inner: Token::RParen, .. }) = tokens.peek_nth(1) {
+                tokens.advance();
+                tokens.advance();
+
+                return Ok(Some(Span::new(
+                    Self::List(List { elems: Vec::new() }),
+                    (determinant.range.start)..(r_paren.range.end),
+                )));
+            }
+
             return Expr::parse(tokens)
                 .map(|expr| expr.map(Self::Expr))
                 .map(Some);
@@ -169,13 +236,48 @@ impl Operand {
             return Ok(None);
         }
 
+        if let Token::LBrace = determinant.inner {
+            return List::parse(tokens)
+                .map(|list| list.map(Self::List))
+                .map(Some);
+        }
+
+        if let Token::Dollar = determinant.inner {
+            return Expr::parse_recall(tokens)
+                .map(|expr| expr.map(Self::Expr))
+                .map(Some);
+        }
+
         tokens.advance();
 
-        match determinant.inner {
-            Token::Rational(val) => {
-                let val = Decimal::from_str_exact(val.as_str()).unwrap();
-                Ok(Operand::Rational(Rational { val }))
+        // A minus sign immediately followed by an operand, as in `-x` or `-(+ 1 2)`, negates that
+        // operand. This is sugar for `(sub x)`.
+        if Self::is_minus_sign(&determinant) {
+            let negated = tokens.peek().filter(|next| {
+                (next.range.start == determinant.range.end)
+                    && matches!(next.inner, Token::Symbol(_) | Token::LParen | Token::LBrace | Token::Dollar)
+            });
+
+            if negated.is_some() {
+                // This `unwrap` is OK because the next token begins an operand.
+                let operand = Self::parse(tokens)?.unwrap();
+                let range = (determinant.range.start)..(operand.range.end);
+
+                return Ok(Some(Span::new(
+                    Self::Expr(Expr {
+                        operation: Span::new(Operation { name: "sub".to_string() }, determinant.range),
+                        operands: vec![operand],
+                    }),
+                    range,
+                )));
             }
+        }
+
+        match determinant.inner {
+            Token::Rational(val) => val
+                .parse()
+                .map(|val| Operand::Rational(Rational { val }))
+                .map_err(|_| Error::invalid(err::Subject::Rational)),
             Token::StrLit(content) => Ok(Operand::StrLit(StrLit { content })),
             Token::Symbol(name) => Ok(Operand::Symbol(Symbol { name })),
             _ => Err(Error::expected(err::Subject::Operand)),
@@ -183,11 +285,53 @@ impl Operand {
         .map(|it| Some(Span::new(it, determinant.range.clone())))
         .map_err(|e| Span::new(e, determinant.range))
     }
+
+    /// Determines if a token is a minus sign, as opposed to the symbol `sub` spelled out.
+    fn is_minus_sign(token: &Span<Token>) -> bool {
+        matches!(&token.inner, Token::Symbol(name) if name == "sub") && (token.range.len() == 1)
+    }
+}
+
+/// A list of operands, as in `{1 2 3}`.
+#[derive(Clone, Debug)]
+pub struct List {
+    pub elems: Vec<Span<Operand>>,
+}
+
+impl fmt::Display for List {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elems: Vec<_> = self.elems.iter().map(|it| it.inner.to_string()).collect();
+
+        write!(f, "{{{}}}", elems.join(" "))
+    }
 }
 
-#[derive(Debug)]
+impl List {
+    fn parse(tokens: &mut Tokens) -> Result<Span<Self>, Span<Error>> {
+        // Lists begin with a left brace: '{'.
+        let l_brace = tokens.expect(err::Subject::Token(Some(Token::LBrace)), |token| token.inner == Token::LBrace)?;
+
+        let mut elems = Vec::new();
+        while tokens.peek().is_some_and(|it| it.inner != Token::RBrace) {
+            // A right parenthesis ends the elements, and is then rejected below.
+            let Some(elem) = Operand::parse(tokens)? else {
+                break;
+            };
+            elems.push(elem);
+        }
+
+        // Lists end with a right brace: '}'.
+        let r_brace = tokens.expect(err::Subject::Token(Some(Token::RBrace)), |token| {
+            token.inner == Token::RBrace
+        })?;
+
+        Ok(Span::new(Self { elems }, (l_brace.range.start)..(r_brace.range.end)))
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Rational {
-    pub val: Decimal,
+    pub val: Number,
 }
 
 impl fmt::Display for Rational {
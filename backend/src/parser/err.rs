@@ -72,6 +72,8 @@ pub enum Subject {
     /// An [operand](crate::parser::Operand).
     Operand,
     Operation,
+    /// A [rational number](crate::parser::Rational).
+    Rational,
     /// A [lexical token](crate::lexer::Token).
     Token(Option<Token>),
 }
@@ -81,6 +83,7 @@ impl fmt::Display for Subject {
         match self {
             Self::Operand => write!(f, "operand"),
             Self::Operation => write!(f, "operation"),
+            Self::Rational => write!(f, "rational number"),
             Self::Token(maybe) => {
                 write!(f, "token")?;
                 if let Some(it) = maybe {
@@ -67,6 +67,11 @@ impl Tokens {
         self.inner.get(self.idx).cloned()
     }
 
+    /// Peeks at the token `n` places after the next one.
+    pub fn peek_nth(&self, n: usize) -> Option<Span<Token>> {
+        self.inner.get(self.idx + n).cloned()
+    }
+
     pub fn next(&mut self) -> Option<Span<Token>> {
         let next = self.peek();
         self.advance();
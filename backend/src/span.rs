@@ -4,7 +4,7 @@
 
 use std::ops::Range;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Span<T> {
     pub inner: T,
     pub range: Range<usize>,
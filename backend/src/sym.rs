@@ -0,0 +1,161 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Symbolic manipulation of IMPL expressions.
+//!
+//! Expressions are manipulated in their parsed form, as trees of [operands](parser::Operand).
+//! Synthesized nodes are spanned to the expression from which they were derived.
+
+mod deriv;
+mod simplify;
+mod solve;
+
+pub use deriv::deriv;
+pub use simplify::simplify;
+pub use solve::{polynomial_roots, solve, Solution};
+
+use crate::{
+    num::Number,
+    parser::{self, Expr, Rational, Symbol},
+    span::Span,
+};
+use std::{fmt, ops::Range};
+
+/// A node of an expression tree.
+pub type Node = Span<parser::Operand>;
+
+/// An error produced by symbolic manipulation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// An operation has no known derivative.
+    NotDifferentiable { name: String },
+    /// An equation is a polynomial or rational one of a degree too high to be solved.
+    DegreeTooHigh { max: i64 },
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotDifferentiable { name } => {
+                write!(f, "cannot differentiate operation \"{}\"", name)
+            }
+            Self::DegreeTooHigh { max } => {
+                write!(f, "cannot solve equations of degree greater than {}", max)
+            }
+        }
+    }
+}
+
+/// Creates an expression that applies an operation to operands.
+pub fn apply(name: &str, operands: Vec<Node>, range: Range<usize>) -> Node {
+    let operation = Span::new(parser::Operation { name: name.to_string() }, range.clone());
+
+    Span::new(parser::Operand::Expr(Expr { operation, operands }), range)
+}
+
+pub fn number(val: Number, range: Range<usize>) -> Node {
+    Span::new(parser::Operand::Rational(Rational { val }), range)
+}
+
+pub fn symbol(name: &str, range: Range<usize>) -> Node {
+    Span::new(parser::Operand::Symbol(Symbol { name: name.to_string() }), range)
+}
+
+/// The value of a node, if it is a number.
+pub fn as_number(node: &Node) -> Option<&Number> {
+    match &node.inner {
+        parser::Operand::Rational(it) => Some(&it.val),
+        _ => None,
+    }
+}
+
+/// The name to which a node refers, if it is a symbol or an expression consisting only of a name,
+/// such as `(x)`.
+pub fn as_name(node: &Node) -> Option<&str> {
+    match &node.inner {
+        parser::Operand::Symbol(it) => Some(it.name.as_str()),
+        parser::Operand::Expr(it) if it.operands.is_empty() => Some(it.operation.inner.name.as_str()),
+        _ => None,
+    }
+}
+
+/// Determines if a node refers to the given name anywhere within it.
+pub fn contains(node: &Node, name: &str) -> bool {
+    if as_name(node) == Some(name) {
+        return true;
+    }
+
+    match &node.inner {
+        parser::Operand::Expr(it) => it.operands.iter().any(|operand| contains(operand, name)),
+        parser::Operand::List(it) => it.elems.iter().any(|elem| contains(elem, name)),
+        _ => false,
+    }
+}
+
+/// Determines if a node is the given exact integer.
+fn is_integer(node: &Node, val: i64) -> bool {
+    as_number(node).is_some_and(|it| it.is_exact() && (*it == Number::from_integer(val)))
+}
+
+/// Creates the sum of terms, omitting those that are zero.
+fn sum(terms: Vec<Node>, range: Range<usize>) -> Node {
+    let mut terms: Vec<_> = terms.into_iter().filter(|it| !is_integer(it, 0)).collect();
+    match terms.len() {
+        0 => number(Number::from_integer(0), range),
+        1 => terms.remove(0),
+        _ => apply("add", terms, range),
+    }
+}
+
+/// Creates the difference of two terms.
+fn difference(a: Node, b: Node, range: Range<usize>) -> Node {
+    if is_integer(&b, 0) {
+        a
+    } else if is_integer(&a, 0) {
+        negation(b, range)
+    } else {
+        apply("sub", vec![a, b], range)
+    }
+}
+
+/// Creates the product of factors, omitting those that are one.
+fn product(factors: Vec<Node>, range: Range<usize>) -> Node {
+    if factors.iter().any(|it| is_integer(it, 0)) {
+        return number(Number::from_integer(0), range);
+    }
+
+    let mut factors: Vec<_> = factors.into_iter().filter(|it| !is_integer(it, 1)).collect();
+    match factors.len() {
+        0 => number(Number::from_integer(1), range),
+        1 => factors.remove(0),
+        _ => apply("mul", factors, range),
+    }
+}
+
+fn negation(a: Node, range: Range<usize>) -> Node {
+    match as_number(&a) {
+        Some(it) => number(it.neg(), range),
+        None => product(vec![number(Number::from_integer(-1), range.clone()), a], range),
+    }
+}
+
+fn quotient(a: Node, b: Node, range: Range<usize>) -> Node {
+    if is_integer(&a, 0) || is_integer(&b, 1) {
+        a
+    } else {
+        apply("div", vec![a, b], range)
+    }
+}
+
+fn power(base: Node, exp: Node, range: Range<usize>) -> Node {
+    if is_integer(&exp, 0) {
+        number(Number::from_integer(1), range)
+    } else if is_integer(&exp, 1) {
+        base
+    } else {
+        apply("pow", vec![base, exp], range)
+    }
+}
@@ -0,0 +1,224 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Symbolic differentiation.
+
+use super::{
+    apply,
+    as_name,
+    as_number,
+    contains,
+    difference,
+    negation,
+    number,
+    power,
+    product,
+    quotient,
+    sum,
+    symbol,
+    Error,
+    Node,
+};
+use crate::{evaluator::env::AngleMode, num::Number, parser};
+use std::ops::Range;
+
+/// Differentiates an expression with respect to a variable.
+///
+/// Names other than the variable are treated as constants. Trigonometric operations measure angles
+/// in the given angle mode, so their derivatives are scaled accordingly.
+pub fn deriv(node: &Node, var: &str, mode: AngleMode) -> Result<Node, Error> {
+    let range = node.range.clone();
+    let int = |val: i64| number(Number::from_integer(val), range.clone());
+
+    if !contains(node, var) {
+        return Ok(int(0));
+    }
+    if as_name(node) == Some(var) {
+        return Ok(int(1));
+    }
+
+    // Lists are differentiated element-wise.
+    if let parser::Operand::List(it) = &node.inner {
+        let elems = it.elems.iter().map(|elem| deriv(elem, var, mode)).collect::<Result<_, _>>()?;
+
+        return Ok(Node::new(parser::Operand::List(parser::List { elems }), range));
+    }
+
+    // Only expressions and lists contain names other than themselves.
+    let parser::Operand::Expr(expr) = &node.inner else {
+        return Ok(int(0));
+    };
+    let name = expr.operation.inner.name.as_str();
+    let d = |it: &Node| deriv(it, var, mode);
+    let call = |name: &str, it: &Node| apply(name, vec![it.clone()], range.clone());
+    let square = |it: &Node| power(it.clone(), int(2), range.clone());
+
+    let result = match (name, expr.operands.as_slice()) {
+        ("add", operands) => {
+            sum(operands.iter().map(d).collect::<Result<_, _>>()?, range)
+        }
+        ("sub", [a]) => negation(d(a)?, range),
+        ("sub", [a, rest @ ..]) => {
+            let rest = rest.iter().map(d).collect::<Result<_, _>>()?;
+            difference(d(a)?, sum(rest, range.clone()), range)
+        }
+        ("mul", operands) => {
+            // The product rule.
+            let mut terms = Vec::new();
+            for (idx, operand) in operands.iter().enumerate() {
+                let mut factors = operands.to_vec();
+                factors[idx] = d(operand)?;
+                terms.push(product(factors, range.clone()));
+            }
+
+            sum(terms, range)
+        }
+        ("div", [a, b, rest @ ..]) => {
+            // `(div a b c)` is `(div a (mul b c))`.
+            let b = if rest.is_empty() {
+                b.clone()
+            } else {
+                product(std::iter::once(b).chain(rest).cloned().collect(), range.clone())
+            };
+
+            // The quotient rule.
+            let numer = difference(
+                product(vec![d(a)?, b.clone()], range.clone()),
+                product(vec![a.clone(), d(&b)?], range.clone()),
+                range.clone(),
+            );
+            quotient(numer, square(&b), range)
+        }
+        ("pow", [base, exp]) if !contains(exp, var) => {
+            // The power rule.
+            let exp_less_one = match as_number(exp) {
+                Some(it) => number(
+                    it.checked_sub(&Number::from_integer(1)).unwrap_or_else(|| it.clone()),
+                    range.clone(),
+                ),
+                None => difference(exp.clone(), int(1), range.clone()),
+            };
+
+            product(vec![exp.clone(), power(base.clone(), exp_less_one, range.clone()), d(base)?], range)
+        }
+        ("pow", [base, exp]) if !contains(base, var) => {
+            product(vec![node.clone(), call("ln", base), d(exp)?], range)
+        }
+        ("pow", [base, exp]) => {
+            // The generalized power rule: (f^g)' = f^g (g' ln f + g f' / f).
+            let inner = sum(
+                vec![
+                    product(vec![d(exp)?, call("ln", base)], range.clone()),
+                    quotient(product(vec![exp.clone(), d(base)?], range.clone()), base.clone(), range.clone()),
+                ],
+                range.clone(),
+            );
+
+            product(vec![node.clone(), inner], range)
+        }
+        ("sqrt", [a]) => {
+            quotient(d(a)?, product(vec![int(2), node.clone()], range.clone()), range)
+        }
+        ("exp", [a]) => {
+            product(vec![node.clone(), d(a)?], range)
+        }
+        ("ln", [a]) => {
+            quotient(d(a)?, a.clone(), range)
+        }
+        ("log", [a]) => {
+            quotient(d(a)?, product(vec![a.clone(), call("ln", &int(10))], range.clone()), range)
+        }
+        ("log", [a, base]) if !contains(base, var) => {
+            quotient(d(a)?, product(vec![a.clone(), call("ln", base)], range.clone()), range)
+        }
+        ("log", [a, base]) => {
+            deriv(&apply("div", vec![call("ln", a), call("ln", base)], range.clone()), var, mode)?
+        }
+        ("sin", [a]) => {
+            product(vec![call("cos", a), d(a)?, to_radians(mode, range.clone())], range)
+        }
+        ("cos", [a]) => {
+            negation(product(vec![call("sin", a), d(a)?, to_radians(mode, range.clone())], range.clone()), range)
+        }
+        ("tan", [a]) => {
+            let numer = product(vec![d(a)?, to_radians(mode, range.clone())], range.clone());
+            quotient(numer, square(&call("cos", a)), range)
+        }
+        ("arcsin", [a]) | ("arccos", [a]) => {
+            let numer = product(vec![d(a)?, from_radians(mode, range.clone())], range.clone());
+            let denom = call("sqrt", &difference(int(1), square(a), range.clone()));
+            let result = quotient(numer, denom, range.clone());
+
+            if name == "arccos" {
+                negation(result, range)
+            } else {
+                result
+            }
+        }
+        ("arctan", [a]) => {
+            let numer = product(vec![d(a)?, from_radians(mode, range.clone())], range.clone());
+            quotient(numer, sum(vec![int(1), square(a)], range.clone()), range)
+        }
+        ("atan2", [y, x]) => {
+            let numer = difference(
+                product(vec![x.clone(), d(y)?], range.clone()),
+                product(vec![y.clone(), d(x)?], range.clone()),
+                range.clone(),
+            );
+            let numer = product(vec![numer, from_radians(mode, range.clone())], range.clone());
+            quotient(numer, sum(vec![square(x), square(y)], range.clone()), range)
+        }
+        // Angle conversions are linear.
+        ("deg", [a]) | ("rad", [a]) | ("grad", [a]) => {
+            call(name, &d(a)?)
+        }
+        ("sinh", [a]) => {
+            product(vec![call("cosh", a), d(a)?], range)
+        }
+        ("cosh", [a]) => {
+            product(vec![call("sinh", a), d(a)?], range)
+        }
+        ("tanh", [a]) => {
+            quotient(d(a)?, square(&call("cosh", a)), range)
+        }
+        ("arsinh", [a]) => {
+            quotient(d(a)?, call("sqrt", &sum(vec![square(a), int(1)], range.clone())), range)
+        }
+        ("arcosh", [a]) => {
+            quotient(d(a)?, call("sqrt", &difference(square(a), int(1), range.clone())), range)
+        }
+        ("artanh", [a]) => {
+            quotient(d(a)?, difference(int(1), square(a), range.clone()), range)
+        }
+        _ => {
+            return Err(Error::NotDifferentiable { name: name.to_string() });
+        }
+    };
+
+    Ok(result)
+}
+
+/// The factor that converts an angle in the given angle mode to radians.
+fn to_radians(mode: AngleMode, range: Range<usize>) -> Node {
+    match mode.turn() {
+        Some(turn) => quotient(
+            symbol("pi", range.clone()),
+            number(Number::from_integer(turn / 2), range.clone()),
+            range,
+        ),
+        None => number(Number::from_integer(1), range),
+    }
+}
+
+/// The factor that converts an angle in radians to the given angle mode.
+fn from_radians(mode: AngleMode, range: Range<usize>) -> Node {
+    match mode.turn() {
+        Some(turn) => quotient(
+            number(Number::from_integer(turn / 2), range.clone()),
+            symbol("pi", range.clone()),
+            range,
+        ),
+        None => number(Number::from_integer(1), range),
+    }
+}
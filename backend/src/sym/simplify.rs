@@ -0,0 +1,612 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Algebraic simplification.
+//!
+//! Expressions are translated into [terms](Term), in which subtraction, division, and square roots
+//! are expressed through sums, products, and powers. Terms are simplified bottom-up and translated
+//! back into expressions in a canonical form.
+
+use super::{apply, number, symbol, Node};
+use crate::{num::Number, parser};
+use std::{cmp::Ordering, ops::Range};
+
+/// Simplifies an expression.
+///
+/// `fold` evaluates expressions whose operands are all numbers, such as `(sin 0)`; their values
+/// replace them if they are exact or if any operand is inexact.
+pub fn simplify(node: &Node, fold: &mut dyn FnMut(&Node) -> Option<Number>) -> Node {
+    let range = node.range.clone();
+    let term = Term::from_node(node);
+
+    Simplifier { fold, range: range.clone() }.simplify(term).into_node(&range)
+}
+
+/// A term of an algebraic expression.
+#[derive(Clone, Debug, PartialEq)]
+enum Term {
+    Num(Number),
+    Sym(String),
+    Str(String),
+    List(Vec<Term>),
+    Add(Vec<Term>),
+    Mul(Vec<Term>),
+    Pow(Box<Term>, Box<Term>),
+    /// The application of any other operation.
+    Call(String, Vec<Term>),
+}
+
+impl Term {
+    fn int(val: i64) -> Self {
+        Self::Num(Number::from_integer(val))
+    }
+
+    fn from_node(node: &Node) -> Self {
+        let expr = match &node.inner {
+            parser::Operand::Expr(it) => it,
+            parser::Operand::List(it) => return Self::List(it.elems.iter().map(Self::from_node).collect()),
+            parser::Operand::Rational(it) => return Self::Num(it.val.clone()),
+            parser::Operand::StrLit(it) => return Self::Str(it.content.clone()),
+            parser::Operand::Symbol(it) => return Self::Sym(it.name.clone()),
+        };
+
+        let name = expr.operation.inner.name.as_str();
+        let mut operands: Vec<_> = expr.operands.iter().map(Self::from_node).collect();
+        match (name, operands.len()) {
+            (_, 0) => Self::Sym(name.to_string()),
+            ("add", _) => Self::Add(operands),
+            ("sub", 1) => Self::Mul(vec![Self::int(-1), operands.remove(0)]),
+            ("sub", _) => {
+                let first = operands.remove(0);
+                let rest = operands.into_iter().map(|it| Self::Mul(vec![Self::int(-1), it]));
+
+                Self::Add(std::iter::once(first).chain(rest).collect())
+            }
+            ("mul", _) => Self::Mul(operands),
+            ("div", 2..) => {
+                let first = operands.remove(0);
+                let rest = operands.into_iter().map(|it| Self::Pow(Box::new(it), Box::new(Self::int(-1))));
+
+                Self::Mul(std::iter::once(first).chain(rest).collect())
+            }
+            ("pow", 2) => {
+                let exp = operands.pop().unwrap_or_else(|| Self::int(1));
+                let base = operands.pop().unwrap_or_else(|| Self::int(1));
+
+                Self::Pow(Box::new(base), Box::new(exp))
+            }
+            ("sqrt", 1) => {
+                let base = operands.remove(0);
+
+                Self::Pow(Box::new(base), Box::new(Self::Num(Number::from_ratio(1, 2))))
+            }
+            _ => Self::Call(name.to_string(), operands),
+        }
+    }
+
+    fn as_num(&self) -> Option<&Number> {
+        match self {
+            Self::Num(it) => Some(it),
+            _ => None,
+        }
+    }
+
+    fn is_int(&self, val: i64) -> bool {
+        self.as_num()
+            .is_some_and(|it| it.is_exact() && (*it == Number::from_integer(val)))
+    }
+
+    /// Splits this term into its numeric coefficient and the remaining factors.
+    fn split_coef(self) -> (Number, Term) {
+        match self {
+            Self::Num(it) => (it, Self::int(1)),
+            Self::Mul(mut factors) if matches!(factors.first(), Some(Self::Num(_))) => {
+                let Self::Num(coef) = factors.remove(0) else {
+                    unreachable!();
+                };
+                let rest = if factors.len() == 1 {
+                    factors.remove(0)
+                } else {
+                    Self::Mul(factors)
+                };
+
+                (coef, rest)
+            }
+            it => (Number::from_integer(1), it),
+        }
+    }
+
+    /// Determines if this term has a negative numeric coefficient.
+    fn is_negative(&self) -> bool {
+        match self {
+            Self::Num(it) => it.is_negative(),
+            Self::Mul(factors) => factors.first().and_then(Self::as_num).is_some_and(Number::is_negative),
+            _ => false,
+        }
+    }
+
+    /// Determines if this term is known to be positive whatever the values of its variables.
+    fn is_positive(&self) -> bool {
+        match self {
+            Self::Num(it) => !it.is_negative() && !it.is_zero(),
+            Self::Sym(it) => matches!(it.as_str(), "e" | "pi"),
+            Self::Add(terms) | Self::Mul(terms) => terms.iter().all(Self::is_positive),
+            Self::Pow(base, _) => base.is_positive(),
+            Self::Call(name, _) => name == "exp",
+            _ => false,
+        }
+    }
+
+    /// Determines if this term is an even integer.
+    fn is_even(&self) -> bool {
+        self.as_num().is_some_and(|it| {
+            it.is_exact() && it.checked_div(&Number::from_integer(2)).is_some_and(|half| half.is_integer())
+        })
+    }
+
+    /// Negates this term, which must have a negative coefficient, for display.
+    fn negate(self) -> Term {
+        let (coef, rest) = self.split_coef();
+        let coef = coef.neg();
+        if rest.is_int(1) {
+            return Self::Num(coef);
+        }
+        if coef == Number::from_integer(1) {
+            return rest;
+        }
+
+        match rest {
+            Self::Mul(mut factors) => {
+                factors.insert(0, Self::Num(coef));
+                Self::Mul(factors)
+            }
+            it => Self::Mul(vec![Self::Num(coef), it]),
+        }
+    }
+
+    /// The total degree of this term in all of its names, used to order terms.
+    fn degree(&self) -> f64 {
+        match self {
+            Self::Sym(_) => 1.,
+            Self::Add(terms) => terms.iter().map(Self::degree).fold(0., f64::max),
+            Self::Mul(factors) => factors.iter().map(Self::degree).sum(),
+            Self::Pow(base, exp) => base.degree() * exp.as_num().map_or(1., Number::to_f64),
+            _ => 0.,
+        }
+    }
+
+    fn into_node(self, range: &Range<usize>) -> Node {
+        match self {
+            Self::Num(it) => number(it, range.clone()),
+            Self::Sym(it) => symbol(it.as_str(), range.clone()),
+            Self::Str(content) => Node::new(parser::Operand::StrLit(parser::StrLit { content }), range.clone()),
+            Self::List(elems) => {
+                let elems = elems.into_iter().map(|it| it.into_node(range)).collect();
+                Node::new(parser::Operand::List(parser::List { elems }), range.clone())
+            }
+            Self::Add(terms) => add_into_node(terms, range),
+            Self::Mul(factors) => mul_into_node(factors, range),
+            Self::Pow(base, exp) => {
+                if exp.as_num().is_some_and(Number::is_negative) {
+                    return mul_into_node(vec![Self::Pow(base, exp)], range);
+                }
+                if exp.as_num() == Some(&Number::from_ratio(1, 2)) {
+                    return apply("sqrt", vec![base.into_node(range)], range.clone());
+                }
+
+                apply("pow", vec![base.into_node(range), exp.into_node(range)], range.clone())
+            }
+            Self::Call(name, args) => {
+                apply(name.as_str(), args.into_iter().map(|it| it.into_node(range)).collect(), range.clone())
+            }
+        }
+    }
+}
+
+/// Renders a sum, subtracting terms with negative coefficients.
+fn add_into_node(terms: Vec<Term>, range: &Range<usize>) -> Node {
+    let (mut positive, negative): (Vec<_>, Vec<_>) = terms.into_iter().partition(|it| !it.is_negative());
+    let mut negative: Vec<_> = negative.into_iter().map(Term::negate).collect();
+
+    let first = match positive.len() {
+        0 => {
+            // There is nothing from which to subtract the first negative term.
+            let first = negative.remove(0);
+            Term::Mul(vec![Term::int(-1), first]).into_node(range)
+        }
+        1 => positive.remove(0).into_node(range),
+        _ => apply("add", positive.into_iter().map(|it| it.into_node(range)).collect(), range.clone()),
+    };
+
+    if negative.is_empty() {
+        return first;
+    }
+
+    let operands = std::iter::once(first)
+        .chain(negative.into_iter().map(|it| it.into_node(range)))
+        .collect();
+    apply("sub", operands, range.clone())
+}
+
+/// Renders a product, dividing by factors with negative exponents and the denominator of the
+/// coefficient.
+fn mul_into_node(factors: Vec<Term>, range: &Range<usize>) -> Node {
+    let mut numer = Vec::new();
+    let mut denom = Vec::new();
+    for factor in factors {
+        match factor {
+            Term::Num(Number::Exact(it)) if !it.is_integer() => {
+                if *it.numer() != 1.into() {
+                    numer.push(Term::Num(Number::Exact(it.numer().clone().into())));
+                }
+                denom.push(Term::Num(Number::Exact(it.denom().clone().into())));
+            }
+            Term::Pow(base, exp) if exp.as_num().is_some_and(Number::is_negative) => {
+                let exp = exp.as_num().map(Number::neg).unwrap_or_else(|| Number::from_integer(1));
+                denom.push(if exp == Number::from_integer(1) {
+                    *base
+                } else {
+                    Term::Pow(base, Box::new(Term::Num(exp)))
+                });
+            }
+            it => numer.push(it),
+        }
+    }
+
+    let product = |mut factors: Vec<Term>| match factors.len() {
+        0 => number(Number::from_integer(1), range.clone()),
+        1 => factors.remove(0).into_node(range),
+        _ => apply("mul", factors.into_iter().map(|it| it.into_node(range)).collect(), range.clone()),
+    };
+
+    if denom.is_empty() {
+        product(numer)
+    } else {
+        apply("div", vec![product(numer), product(denom)], range.clone())
+    }
+}
+
+/// Orders terms canonically: by descending degree, then by their textual form, with numbers last.
+fn canonical_order(a: &Term, b: &Term, range: &Range<usize>) -> Ordering {
+    let key = |it: &Term| matches!(it, Term::Num(_));
+
+    key(a)
+        .cmp(&key(b))
+        .then_with(|| b.degree().total_cmp(&a.degree()))
+        .then_with(|| {
+            let a = a.clone().into_node(range).inner.to_string();
+            let b = b.clone().into_node(range).inner.to_string();
+
+            a.cmp(&b)
+        })
+}
+
+struct Simplifier<'a> {
+    fold: &'a mut dyn FnMut(&Node) -> Option<Number>,
+    range: Range<usize>,
+}
+
+impl Simplifier<'_> {
+    fn simplify(&mut self, term: Term) -> Term {
+        match term {
+            Term::Add(terms) => {
+                let terms = terms.into_iter().map(|it| self.simplify(it)).collect();
+                self.add(terms)
+            }
+            Term::Mul(factors) => {
+                let factors = factors.into_iter().map(|it| self.simplify(it)).collect();
+                self.mul(factors)
+            }
+            Term::Pow(base, exp) => {
+                let base = self.simplify(*base);
+                let exp = self.simplify(*exp);
+                self.pow(base, exp)
+            }
+            Term::Call(name, args) => {
+                let args = args.into_iter().map(|it| self.simplify(it)).collect();
+                self.call(name, args)
+            }
+            Term::List(elems) => Term::List(elems.into_iter().map(|it| self.simplify(it)).collect()),
+            it => it,
+        }
+    }
+
+    /// Simplifies a sum by folding constants and collecting like terms.
+    fn add(&mut self, terms: Vec<Term>) -> Term {
+        let mut constant = Number::from_integer(0);
+        // Terms without a numeric coefficient, and the sum of the coefficients they appeared with.
+        let mut like: Vec<(Term, Number)> = Vec::new();
+        let mut others = Vec::new();
+
+        for term in flatten(terms, |it| matches!(it, Term::Add(_))) {
+            if let Term::Num(it) = &term {
+                match constant.checked_add(it) {
+                    Some(sum) => constant = sum,
+                    None => others.push(term),
+                }
+                continue;
+            }
+
+            let (coef, rest) = term.split_coef();
+            match like.iter_mut().find(|(it, _)| *it == rest) {
+                Some((_, sum)) => match sum.checked_add(&coef) {
+                    Some(it) => *sum = it,
+                    None => others.push(Term::Mul(vec![Term::Num(coef), rest])),
+                },
+                None => like.push((rest, coef)),
+            }
+        }
+
+        // sin²(a) + cos²(a) = 1.
+        let mut idx = 0;
+        while idx < like.len() {
+            let partner = match &like[idx].0 {
+                Term::Pow(base, exp) if exp.is_int(2) => match base.as_ref() {
+                    Term::Call(name, args) if name == "sin" => {
+                        let cos_sq = Term::Pow(Box::new(Term::Call("cos".to_string(), args.clone())), exp.clone());
+                        like.iter().position(|(it, coef)| (*it == cos_sq) && (*coef == like[idx].1))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+
+            match partner.zip(constant.checked_add(&like[idx].1)) {
+                Some((partner, sum)) => {
+                    constant = sum;
+                    let _ = like.remove(idx.max(partner));
+                    let _ = like.remove(idx.min(partner));
+                }
+                None => {
+                    idx += 1;
+                }
+            }
+        }
+
+        let mut terms = others;
+        for (rest, coef) in like {
+            if coef.is_zero() {
+                continue;
+            }
+
+            terms.push(if coef == Number::from_integer(1) {
+                rest
+            } else {
+                self.mul(vec![Term::Num(coef), rest])
+            });
+        }
+        if !constant.is_zero() || !constant.is_exact() {
+            terms.push(Term::Num(constant));
+        }
+
+        let range = self.range.clone();
+        terms.sort_by(|a, b| canonical_order(a, b, &range));
+        match terms.len() {
+            0 => Term::int(0),
+            1 => terms.remove(0),
+            _ => Term::Add(terms),
+        }
+    }
+
+    /// Simplifies a product by folding constants and combining the exponents of like bases.
+    fn mul(&mut self, factors: Vec<Term>) -> Term {
+        let mut coef = Number::from_integer(1);
+        // Bases and the exponents with which they appeared.
+        let mut bases: Vec<(Term, Vec<Term>)> = Vec::new();
+        let mut others = Vec::new();
+
+        for factor in flatten(factors, |it| matches!(it, Term::Mul(_))) {
+            let (base, exp) = match factor {
+                Term::Num(it) => {
+                    match coef.checked_mul(&it) {
+                        Some(product) => coef = product,
+                        None => others.push(Term::Num(it)),
+                    }
+                    continue;
+                }
+                Term::Pow(base, exp) => (*base, *exp),
+                it => (it, Term::int(1)),
+            };
+
+            match bases.iter_mut().find(|(it, _)| *it == base) {
+                Some((_, exps)) => exps.push(exp),
+                None => bases.push((base, vec![exp])),
+            }
+        }
+
+        let mut factors = others;
+        for (base, exps) in bases {
+            match self.add(exps) {
+                exp if exp.is_int(0) => {}
+                exp => match self.pow(base, exp) {
+                    Term::Num(it) => match coef.checked_mul(&it) {
+                        Some(product) => coef = product,
+                        None => factors.push(Term::Num(it)),
+                    },
+                    it => factors.push(it),
+                },
+            }
+        }
+
+        if coef.is_zero() {
+            return Term::Num(coef);
+        }
+
+        // A numeric coefficient is distributed over a sum, so that `(* 2 (+ x 1))` is `2x + 2`.
+        if (factors.len() == 1) && matches!(factors[0], Term::Add(_)) && (coef != Number::from_integer(1)) {
+            let Some(Term::Add(terms)) = factors.pop() else {
+                unreachable!();
+            };
+            let terms = terms
+                .into_iter()
+                .map(|it| self.mul(vec![Term::Num(coef.clone()), it]))
+                .collect();
+
+            return self.add(terms);
+        }
+
+        let range = self.range.clone();
+        factors.sort_by(|a, b| canonical_order(a, b, &range));
+        if factors.is_empty() {
+            return Term::Num(coef);
+        }
+        if coef != Number::from_integer(1) {
+            factors.insert(0, Term::Num(coef));
+        }
+
+        match factors.len() {
+            1 => factors.remove(0),
+            _ => Term::Mul(factors),
+        }
+    }
+
+    fn pow(&mut self, base: Term, exp: Term) -> Term {
+        if exp.is_int(0) || base.is_int(1) {
+            return Term::int(1);
+        }
+        if exp.is_int(1) {
+            return base;
+        }
+
+        match (base, exp) {
+            (Term::Num(base), Term::Num(exp)) => {
+                match fold_pow(&base, &exp) {
+                    Some(it) => Term::Num(it),
+                    None => Term::Pow(Box::new(Term::Num(base)), Box::new(Term::Num(exp))),
+                }
+            }
+            // (a^m)^n = a^(mn) for integer n.
+            (Term::Pow(base, inner), Term::Num(exp)) if exp.is_integer() => {
+                let exp = self.mul(vec![*inner, Term::Num(exp)]);
+                self.pow(*base, exp)
+            }
+            // (ab)^n = a^n b^n for integer n.
+            (Term::Mul(factors), Term::Num(exp)) if exp.is_integer() => {
+                let factors = factors
+                    .into_iter()
+                    .map(|it| self.pow(it, Term::Num(exp.clone())))
+                    .collect();
+                self.mul(factors)
+            }
+            (base, exp) => Term::Pow(Box::new(base), Box::new(exp)),
+        }
+    }
+
+    /// Simplifies the application of an operation, applying identities and folding constants.
+    fn call(&mut self, name: String, mut args: Vec<Term>) -> Term {
+        match (name.as_str(), args.as_slice()) {
+            ("ln", [Term::Call(inner, it)]) | ("exp", [Term::Call(inner, it)])
+            | ("sin", [Term::Call(inner, it)]) | ("cos", [Term::Call(inner, it)])
+            | ("tan", [Term::Call(inner, it)])
+                if is_inverse(name.as_str(), inner.as_str()) && (it.len() == 1) =>
+            {
+                return it[0].clone();
+            }
+            ("ln", [it]) if it.is_int(1) => {
+                return Term::int(0);
+            }
+            ("ln", [Term::Sym(it)]) if it == "e" => {
+                return Term::int(1);
+            }
+            // ln(b^n) = n ln b only holds for positive b; for an even n, it is n ln |b| instead.
+            ("ln", [Term::Pow(base, exp)]) if base.is_positive() || exp.is_even() => {
+                let base = if base.is_positive() {
+                    *base.clone()
+                } else {
+                    self.call("abs".to_string(), vec![*base.clone()])
+                };
+                let ln = self.call("ln".to_string(), vec![base]);
+                return self.mul(vec![*exp.clone(), ln]);
+            }
+            ("exp", [it]) if it.is_int(0) => {
+                return Term::int(1);
+            }
+            ("log", [a, ..]) if a.is_int(1) => {
+                return Term::int(0);
+            }
+            ("log", [a, b]) if a == b => {
+                return Term::int(1);
+            }
+            // Sine and tangent are odd; cosine is even.
+            ("sin", [it]) | ("tan", [it]) | ("cos", [it]) if it.is_negative() => {
+                let arg = self.mul(vec![Term::int(-1), args.remove(0)]);
+                let result = self.call(name.clone(), vec![arg]);
+
+                return if name == "cos" {
+                    result
+                } else {
+                    self.mul(vec![Term::int(-1), result])
+                };
+            }
+            _ => {}
+        }
+
+        let term = Term::Call(name, args);
+        if let Term::Call(_, args) = &term {
+            let nums: Option<Vec<_>> = args.iter().map(Term::as_num).collect();
+            if let Some(nums) = nums {
+                let any_inexact = nums.iter().any(|it| !it.is_exact());
+                if let Some(val) = (self.fold)(&term.clone().into_node(&self.range)) {
+                    if val.is_exact() || any_inexact {
+                        return Term::Num(val);
+                    }
+                }
+            }
+        }
+
+        term
+    }
+}
+
+/// Determines if `inner` is the inverse of `outer`, so that `(outer (inner a))` is `a`.
+fn is_inverse(outer: &str, inner: &str) -> bool {
+    matches!(
+        (outer, inner),
+        ("ln", "exp") | ("exp", "ln") | ("sin", "arcsin") | ("cos", "arccos") | ("tan", "arctan"),
+    )
+}
+
+/// Raises a number to a numeric power, or returns `None` if the result cannot be represented as a
+/// number without losing exactness.
+fn fold_pow(base: &Number, exp: &Number) -> Option<Number> {
+    if base.is_zero() && exp.is_negative() {
+        return None;
+    }
+
+    if exp.is_integer() {
+        let exp = i32::try_from(exp.to_i64()?).ok()?;
+        return base.checked_powi(exp);
+    }
+
+    if base.is_negative() {
+        return None;
+    }
+
+    if *exp == Number::from_ratio(1, 2) {
+        return base.sqrt().filter(|it| it.is_exact() || !base.is_exact());
+    }
+
+    if base.is_exact() && exp.is_exact() {
+        return None;
+    }
+
+    Number::approx(base.to_f64().powf(exp.to_f64()))
+}
+
+/// Flattens nested terms of the same associative operation.
+fn flatten(terms: Vec<Term>, is_nested: impl Fn(&Term) -> bool + Copy) -> Vec<Term> {
+    let mut flat = Vec::new();
+    for term in terms {
+        if is_nested(&term) {
+            let (Term::Add(inner) | Term::Mul(inner)) = term else {
+                unreachable!();
+            };
+            flat.extend(flatten(inner, is_nested));
+        } else {
+            flat.push(term);
+        }
+    }
+
+    flat
+}
@@ -0,0 +1,514 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Exact solution of polynomial and rational equations.
+
+use super::{apply, as_name, as_number, contains, negation, number, sum, symbol, Error, Node};
+use crate::{num::Number, parser};
+use num_bigint::BigInt;
+use num_integer::Integer as _;
+use num_traits::{One as _, Signed as _, ToPrimitive as _, Zero as _};
+use std::ops::Range;
+
+/// The maximum degree of a polynomial that is solved exactly.
+///
+/// Expanding powers such as `(pow (+ x 1) 1000000)` into coefficients would otherwise exhaust
+/// memory. Equations of a higher degree are rejected rather than solved numerically, as only one of
+/// their roots would be found.
+const MAX_DEGREE: i64 = 64;
+
+/// The largest magnitude of the constant and leading coefficients for which rational roots are
+/// searched by enumerating divisors.
+const MAX_DIVISOR_SEARCH: i64 = 1_000_000_000;
+
+/// The solutions of a polynomial or rational equation.
+#[derive(Debug)]
+pub enum Solution {
+    /// Every value of the variable is a solution.
+    Identity,
+    /// No value of the variable is a solution, as the equation reduces to a false one between
+    /// constants or to a fraction with a constant, nonzero numerator, as in `1/x = 0`.
+    Contradiction,
+    /// The roots, in ascending order of their real and then imaginary parts. Roots are exact where
+    /// possible.
+    ///
+    /// Complex roots are only found for quadratic factors, and are expressed in terms of the
+    /// imaginary unit `i`. Real roots of factors of higher degree are found numerically, so this
+    /// may be empty even if the equation has real solutions.
+    Roots(Vec<Node>),
+}
+
+/// Solves the equation `node = 0` for a variable if `node` is a rational function of it, that is,
+/// a polynomial or a ratio of polynomials.
+///
+/// `fold` evaluates subexpressions that do not contain the variable; they must evaluate to numbers
+/// for `node` to be considered a rational function. Returns `Ok(None)` if `node` is not one, and an
+/// error if its degree is too high for it to be solved.
+pub fn solve(
+    node: &Node,
+    var: &str,
+    fold: &mut dyn FnMut(&Node) -> Option<Number>,
+) -> Result<Option<Solution>, Error> {
+    let (mut numer, mut denom) = match fraction(node, var, fold) {
+        Ok(it) => it,
+        Err(Unsupported::NotRational) => return Ok(None),
+        Err(Unsupported::Degree) => return Err(Error::DegreeTooHigh { max: MAX_DEGREE }),
+    };
+    trim(&mut numer);
+    trim(&mut denom);
+    match numer.len() {
+        0 => return Ok(Some(Solution::Identity)),
+        1 => return Ok(Some(Solution::Contradiction)),
+        _ => {}
+    }
+
+    let Some(mut roots) = roots(numer, &node.range) else {
+        return Ok(None);
+    };
+    // A fraction is undefined, rather than zero, where its denominator is also zero.
+    if denom.len() > 1 {
+        roots.retain(|(approx, root)| !vanishes(&denom, *approx, root));
+    }
+
+    Ok(Some(Solution::Roots(ordered(roots))))
+}
+
+/// Finds the distinct roots of a polynomial, given its coefficients in ascending order of degree,
+/// the last of which must be nonzero.
+///
+/// The roots are ordered as in [`Solution::Roots`], and synthesized nodes are spanned to the given
+/// range. Returns `None` if the roots cannot be represented.
+pub fn polynomial_roots(coefs: Vec<Number>, range: Range<usize>) -> Option<Vec<Node>> {
+    roots(coefs, &range).map(ordered)
+}
+
+/// Orders roots as in [`Solution::Roots`] and removes duplicates.
+fn ordered(mut roots: Vec<(Approx, Node)>) -> Vec<Node> {
+    roots.sort_by(|((a_re, a_im), _), ((b_re, b_im), _)| a_re.total_cmp(b_re).then(a_im.total_cmp(b_im)));
+    roots.dedup_by(|(a, _), (b, _)| a == b);
+
+    roots.into_iter().map(|(_, it)| it).collect()
+}
+
+/// The coefficients of a polynomial, in ascending order of degree.
+type Coefs = Vec<Number>;
+
+/// A rational function, as the coefficients of its numerator and denominator.
+type Fraction = (Coefs, Coefs);
+
+/// The reason an expression cannot be expressed as a rational function.
+enum Unsupported {
+    /// The expression is not a rational function of the variable, or its coefficients cannot be
+    /// represented.
+    NotRational,
+    /// The degree of the expression would exceed [`MAX_DEGREE`].
+    Degree,
+}
+
+fn fraction(node: &Node, var: &str, fold: &mut dyn FnMut(&Node) -> Option<Number>) -> Result<Fraction, Unsupported> {
+    use Unsupported::{Degree, NotRational};
+
+    let one = || vec![Number::from_integer(1)];
+    if !contains(node, var) {
+        return fold(node).map(|it| (vec![it], one())).ok_or(NotRational);
+    }
+    if as_name(node) == Some(var) {
+        return Ok((vec![Number::from_integer(0), Number::from_integer(1)], one()));
+    }
+
+    let parser::Operand::Expr(expr) = &node.inner else {
+        return Err(NotRational);
+    };
+    match (expr.operation.inner.name.as_str(), expr.operands.as_slice()) {
+        ("add", operands) => {
+            let mut result = (vec![Number::from_integer(0)], one());
+            for operand in fractions(operands, var, fold)? {
+                result = add_fractions(&result, &operand)?;
+            }
+
+            Ok(result)
+        }
+        ("sub", [a]) => {
+            let (numer, denom) = fraction(a, var, fold)?;
+
+            Ok((scale(&numer, &Number::from_integer(-1)).ok_or(NotRational)?, denom))
+        }
+        ("sub", operands @ [_, _, ..]) => {
+            let mut operands = fractions(operands, var, fold)?.into_iter();
+            // This `unwrap` is OK because there are at least two operands.
+            let mut result = operands.next().unwrap();
+            for (numer, denom) in operands {
+                let negated = (scale(&numer, &Number::from_integer(-1)).ok_or(NotRational)?, denom);
+                result = add_fractions(&result, &negated)?;
+            }
+
+            Ok(result)
+        }
+        ("mul", operands) => {
+            let mut result = (one(), one());
+            for (numer, denom) in fractions(operands, var, fold)? {
+                result = (mul(&result.0, &numer)?, mul(&result.1, &denom)?);
+            }
+
+            Ok(result)
+        }
+        ("div", operands @ [_, _, ..]) => {
+            let mut operands = fractions(operands, var, fold)?.into_iter();
+            // This `unwrap` is OK because there are at least two operands.
+            let mut result = operands.next().unwrap();
+            for (numer, denom) in operands {
+                if numer.iter().all(Number::is_zero) {
+                    return Err(NotRational);
+                }
+                result = (mul(&result.0, &denom)?, mul(&result.1, &numer)?);
+            }
+
+            Ok(result)
+        }
+        ("pow", [base, exp]) if !contains(exp, var) => {
+            let exp = fold(exp)
+                .filter(Number::is_integer)
+                .and_then(|it| it.to_i64())
+                .ok_or(NotRational)?;
+
+            let (mut numer, mut denom) = fraction(base, var, fold)?;
+            if exp < 0 {
+                if numer.iter().all(Number::is_zero) {
+                    return Err(NotRational);
+                }
+                std::mem::swap(&mut numer, &mut denom);
+            }
+            if exp.abs() > MAX_DEGREE {
+                return Err(Degree);
+            }
+
+            let mut result = (one(), one());
+            for _ in 0..exp.abs() {
+                result = (mul(&result.0, &numer)?, mul(&result.1, &denom)?);
+            }
+
+            Ok(result)
+        }
+        _ => Err(NotRational),
+    }
+}
+
+/// Converts each of several operands to a rational function.
+///
+/// If any operand is not a rational function, that is reported in preference to the degree of
+/// another being too high, as the expression as a whole could not be solved exactly regardless.
+fn fractions(
+    operands: &[Node],
+    var: &str,
+    fold: &mut dyn FnMut(&Node) -> Option<Number>,
+) -> Result<Vec<Fraction>, Unsupported> {
+    let results: Vec<_> = operands.iter().map(|it| fraction(it, var, fold)).collect();
+    if results.iter().any(|it| matches!(it, Err(Unsupported::NotRational))) {
+        return Err(Unsupported::NotRational);
+    }
+
+    results.into_iter().collect()
+}
+
+fn add_fractions(a: &Fraction, b: &Fraction) -> Result<Fraction, Unsupported> {
+    // Fractions with the same denominator, including all polynomials, are added directly.
+    if a.1 == b.1 {
+        return Ok((add(&a.0, &b.0).ok_or(Unsupported::NotRational)?, a.1.clone()));
+    }
+
+    let numer = add(&mul(&a.0, &b.1)?, &mul(&b.0, &a.1)?).ok_or(Unsupported::NotRational)?;
+
+    Ok((numer, mul(&a.1, &b.1)?))
+}
+
+/// Removes leading zero coefficients, which are last as coefficients are in ascending order.
+fn trim(coefs: &mut Coefs) {
+    while coefs.last().is_some_and(Number::is_zero) {
+        let _ = coefs.pop();
+    }
+}
+
+fn add(a: &[Number], b: &[Number]) -> Option<Coefs> {
+    let zero = Number::from_integer(0);
+    (0..a.len().max(b.len()))
+        .map(|idx| a.get(idx).unwrap_or(&zero).checked_add(b.get(idx).unwrap_or(&zero)))
+        .collect()
+}
+
+fn mul(a: &[Number], b: &[Number]) -> Result<Coefs, Unsupported> {
+    if (a.len() + b.len()) as i64 > MAX_DEGREE + 2 {
+        return Err(Unsupported::Degree);
+    }
+
+    let mut result = vec![Number::from_integer(0); a.len() + b.len() - 1];
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            result[i + j] = a
+                .checked_mul(b)
+                .and_then(|it| result[i + j].checked_add(&it))
+                .ok_or(Unsupported::NotRational)?;
+        }
+    }
+
+    Ok(result)
+}
+
+fn scale(a: &[Number], factor: &Number) -> Option<Coefs> {
+    a.iter().map(|it| it.checked_mul(factor)).collect()
+}
+
+/// Determines if a polynomial is zero at one of the roots found by [`roots`].
+///
+/// Exact roots of polynomials with exact coefficients are checked exactly, and others by their
+/// approximate values.
+fn vanishes(coefs: &[Number], (re, im): Approx, root: &Node) -> bool {
+    if let Some(root) = as_number(root).filter(|it| it.is_exact()) {
+        if coefs.iter().all(Number::is_exact) {
+            return eval(coefs, root).is_some_and(|it| it.is_zero());
+        }
+    }
+
+    // Evaluate the polynomial in complex arithmetic with Horner's method, alongside the magnitude of
+    // its terms, against which the result is judged to be zero.
+    let modulus = re.hypot(im);
+    let (mut acc_re, mut acc_im, mut magnitude) = (0.0, 0.0, 0.0);
+    for (i, coef) in coefs.iter().enumerate().rev() {
+        let coef = coef.to_f64();
+        (acc_re, acc_im) = ((acc_re * re) - (acc_im * im) + coef, (acc_re * im) + (acc_im * re));
+        magnitude += coef.abs() * modulus.powi(i as i32);
+    }
+
+    acc_re.hypot(acc_im) <= 1e-9 * magnitude
+}
+
+/// Evaluates a polynomial with Horner's method.
+fn eval(coefs: &[Number], x: &Number) -> Option<Number> {
+    coefs
+        .iter()
+        .rev()
+        .try_fold(Number::from_integer(0), |acc, coef| acc.checked_mul(x)?.checked_add(coef))
+}
+
+/// Divides a polynomial by `(x - root)`, discarding the remainder.
+fn deflate(coefs: &[Number], root: &Number) -> Option<Coefs> {
+    let mut result = vec![Number::from_integer(0); coefs.len() - 1];
+    let mut carry = Number::from_integer(0);
+    for idx in (1..coefs.len()).rev() {
+        carry = carry.checked_mul(root)?.checked_add(&coefs[idx])?;
+        result[idx - 1] = carry.clone();
+    }
+
+    Some(result)
+}
+
+/// The approximate real and imaginary parts of a root, by which roots are ordered.
+type Approx = (f64, f64);
+
+/// Finds the roots of a polynomial with a nonzero leading coefficient, paired with their
+/// approximate values for ordering.
+fn roots(mut coefs: Coefs, range: &Range<usize>) -> Option<Vec<(Approx, Node)>> {
+    let mut roots = Vec::new();
+    let push = |roots: &mut Vec<(Approx, Node)>, it: Number| roots.push(((it.to_f64(), 0.0), number(it, range.clone())));
+
+    // Factor out powers of the variable.
+    if coefs.len() > 1 && coefs[0].is_zero() {
+        push(&mut roots, Number::from_integer(0));
+        while coefs.len() > 1 && coefs[0].is_zero() {
+            let _ = coefs.remove(0);
+        }
+    }
+
+    if coefs.iter().all(Number::is_exact) {
+        // Rational roots are divided out so that what remains may be solved by formula.
+        while coefs.len() > 3 {
+            let Some(root) = rational_root(&coefs) else {
+                break;
+            };
+            coefs = deflate(&coefs, &root)?;
+            push(&mut roots, root);
+        }
+    }
+
+    match coefs.len() {
+        1 => {}
+        2 => {
+            push(&mut roots, coefs[0].neg().checked_div(&coefs[1])?);
+        }
+        3 => {
+            roots.extend(quadratic(&coefs[2], &coefs[1], &coefs[0], range)?);
+        }
+        _ => {
+            let coefs: Vec<_> = coefs.iter().map(Number::to_f64).collect();
+            for root in real_roots(&coefs) {
+                push(&mut roots, Number::approx(root)?);
+            }
+        }
+    }
+
+    Some(roots)
+}
+
+/// Finds a rational root of a polynomial with exact coefficients using the rational root theorem.
+fn rational_root(coefs: &[Number]) -> Option<Number> {
+    // Scale the coefficients to integers.
+    let denoms = coefs.iter().filter_map(|it| match it {
+        Number::Exact(it) => Some(it.denom().clone()),
+        Number::Inexact(_) => None,
+    });
+    let lcm = denoms.fold(BigInt::one(), |acc, it| acc.lcm(&it));
+    let ints: Vec<BigInt> = coefs
+        .iter()
+        .map(|it| match it {
+            Number::Exact(it) => Some((it * &lcm).to_integer()),
+            Number::Inexact(_) => None,
+        })
+        .collect::<Option<_>>()?;
+
+    let constant = ints.first()?.abs().to_i64().filter(|it| *it <= MAX_DIVISOR_SEARCH)?;
+    let leading = ints.last()?.abs().to_i64().filter(|it| *it <= MAX_DIVISOR_SEARCH)?;
+    if constant.is_zero() {
+        return Some(Number::from_integer(0));
+    }
+
+    for p in divisors(constant) {
+        for q in divisors(leading) {
+            for sign in [1, -1] {
+                let candidate = Number::from_ratio(sign * p, q);
+                if eval(coefs, &candidate).is_some_and(|it| it.is_zero()) {
+                    return Some(candidate);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn divisors(n: i64) -> Vec<i64> {
+    let mut result = Vec::new();
+    let mut d = 1;
+    while d * d <= n {
+        if n % d == 0 {
+            result.push(d);
+            if d * d != n {
+                result.push(n / d);
+            }
+        }
+        d += 1;
+    }
+
+    result
+}
+
+/// Solves `ax² + bx + c = 0`.
+///
+/// Irrational roots of exact equations are expressed with square roots, and complex roots with the
+/// imaginary unit `i`.
+fn quadratic(a: &Number, b: &Number, c: &Number, range: &Range<usize>) -> Option<Vec<(Approx, Node)>> {
+    let two = Number::from_integer(2);
+    let four = Number::from_integer(4);
+
+    // The roots are `center ± sqrt(radius_sq)`.
+    let center = b.neg().checked_div(&two.checked_mul(a)?)?;
+    let disc = b.checked_mul(b)?.checked_sub(&four.checked_mul(a)?.checked_mul(c)?)?;
+    let radius_sq = disc.checked_div(&four.checked_mul(a)?.checked_mul(a)?)?;
+
+    if radius_sq.is_zero() {
+        return Some(vec![((center.to_f64(), 0.0), number(center, range.clone()))]);
+    }
+
+    // If `radius_sq` is negative, the roots are the complex conjugates `center ± i sqrt(-radius_sq)`.
+    let is_complex = radius_sq.is_negative();
+    let radius_sq = radius_sq.abs();
+    let radius = radius_sq.sqrt()?;
+    let (lo, hi) = if !is_complex && (radius.is_exact() || !radius_sq.is_exact()) {
+        let lo = center.checked_sub(&radius)?;
+        let hi = center.checked_add(&radius)?;
+        (number(lo, range.clone()), number(hi, range.clone()))
+    } else {
+        let mut offset = if radius.is_exact() || !radius_sq.is_exact() {
+            number(radius.clone(), range.clone())
+        } else {
+            apply("sqrt", vec![number(radius_sq, range.clone())], range.clone())
+        };
+        if is_complex {
+            offset = apply("mul", vec![offset, symbol("i", range.clone())], range.clone());
+        }
+
+        let lo = sum(vec![number(center.clone(), range.clone()), negation(offset.clone(), range.clone())], range.clone());
+        let hi = sum(vec![number(center.clone(), range.clone()), offset], range.clone());
+        (lo, hi)
+    };
+
+    let center = center.to_f64();
+    let radius = radius.to_f64();
+    Some(if is_complex {
+        vec![((center, -radius), lo), ((center, radius), hi)]
+    } else {
+        vec![((center - radius, 0.0), lo), ((center + radius, 0.0), hi)]
+    })
+}
+
+/// Finds the real roots of a polynomial numerically.
+///
+/// The roots of the derivative divide the real line into intervals on which the polynomial is
+/// monotonic, so each interval contains at most one root, which is found by bisection.
+fn real_roots(coefs: &[f64]) -> Vec<f64> {
+    if coefs.len() < 2 {
+        return Vec::new();
+    }
+    if coefs.len() == 2 {
+        return vec![-coefs[0] / coefs[1]];
+    }
+
+    let eval = |x: f64| coefs.iter().rev().fold(0.0, |acc, coef| acc * x + coef);
+    // The magnitude of the terms at `x`, against which a residual is judged to be zero.
+    let scale = |x: f64| coefs.iter().enumerate().map(|(i, coef)| (coef * x.powi(i as i32)).abs()).sum::<f64>();
+
+    let leading = coefs[coefs.len() - 1];
+    // Cauchy's bound on the magnitude of the roots.
+    let bound = 1.0 + coefs[..coefs.len() - 1].iter().map(|it| (it / leading).abs()).fold(0.0, f64::max);
+
+    let deriv: Vec<_> = coefs.iter().enumerate().skip(1).map(|(i, coef)| coef * i as f64).collect();
+    let mut points = vec![-bound];
+    points.extend(real_roots(&deriv).into_iter().filter(|it| it.abs() < bound));
+    points.push(bound);
+
+    let mut roots: Vec<f64> = Vec::new();
+    for pair in points.windows(2) {
+        let (mut lo, mut hi) = (pair[0], pair[1]);
+        let (f_lo, f_hi) = (eval(lo), eval(hi));
+
+        if f_lo.abs() <= 1e-12 * scale(lo) {
+            // A multiple root at a critical point.
+            if roots.last() != Some(&lo) {
+                roots.push(lo);
+            }
+            continue;
+        }
+        if f_lo.signum() == f_hi.signum() {
+            continue;
+        }
+
+        for _ in 0..200 {
+            let mid = 0.5 * (lo + hi);
+            if (mid == lo) || (mid == hi) {
+                break;
+            }
+            if eval(mid).signum() == f_lo.signum() {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        roots.push(0.5 * (lo + hi));
+    }
+
+    let last = points[points.len() - 1];
+    if eval(last).abs() <= 1e-12 * scale(last) && roots.last() != Some(&last) {
+        roots.push(last);
+    }
+
+    roots
+}
@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Trigonometry in each angle mode.
+//!
+//! The JSON tests are evaluated in the default mode, radians, so the other modes are tested here.
+
+use imp_backend::{
+    evaluator::{env::AngleMode, Environment, Output},
+    Callbacks,
+    Interpreter,
+};
+
+/// Evaluates each test in a new interpreter with the given angle mode and compares the text of its
+/// last output to the expected text.
+fn check(mode: AngleMode, tests: &[(&str, &str)]) {
+    for (test, expected) in tests {
+        let mut interp = Interpreter::builder().angle_mode(mode).build();
+        let outputs = interp
+            .process(test, Callbacks::default())
+            .unwrap_or_else(|e| panic!("`{}` failed in {:?}: {}", test, mode, e.inner));
+
+        match outputs.last() {
+            Some(Output::Text(it)) => assert_eq!(it, expected, "`{}` in {:?}", test, mode),
+            _ => panic!("`{}` did not produce text in {:?}", test, mode),
+        }
+    }
+}
+
+#[test]
+fn degrees() {
+    check(
+        AngleMode::Degrees,
+        &[
+            ("sin 90", "1"),
+            ("sin 30", "1/2"),
+            ("cos 180", "-1"),
+            ("tan 45", "1"),
+            ("arcsin 1", "90"),
+            ("arcsin (/ 1 2)", "30"),
+            ("arccos 0", "90"),
+            ("arctan 1", "45"),
+            ("atan2 1 0", "90"),
+            ("deg 90", "90"),
+            ("rad pi", "180"),
+            ("grad 100", "90"),
+            ("sin (grad 100)", "1"),
+        ],
+    );
+}
+
+#[test]
+fn gradians() {
+    check(
+        AngleMode::Gradians,
+        &[
+            ("sin 100", "1"),
+            ("cos 200", "-1"),
+            ("tan 50", "1"),
+            ("arcsin 1", "100"),
+            ("arcsin (/ 1 2)", "100/3"),
+            ("arccos 0", "100"),
+            ("arctan 1", "50"),
+            ("atan2 1 0", "100"),
+            ("grad 100", "100"),
+            ("rad pi", "200"),
+            ("deg 90", "100"),
+            ("sin (deg 90)", "1"),
+        ],
+    );
+}
+
+#[test]
+fn mode_may_change_between_inputs() {
+    fn arcsin(env: &mut Environment) -> String {
+        match imp_backend::process("arcsin 1", env, Callbacks::default()) {
+            Ok(outputs) => match outputs.as_slice() {
+                [Output::Text(it)] => it.clone(),
+                _ => panic!("`arcsin 1` did not produce text"),
+            },
+            Err(e) => panic!("`arcsin 1` failed: {}", e.inner),
+        }
+    }
+
+    let mut env = Environment::default();
+    env.set_angle_mode(AngleMode::Degrees);
+    assert_eq!(arcsin(&mut env), "90");
+    env.set_angle_mode(AngleMode::Gradians);
+    assert_eq!(arcsin(&mut env), "100");
+    env.set_angle_mode(AngleMode::Radians);
+    assert_eq!(arcsin(&mut env), "1.5707963267948966");
+}
@@ -0,0 +1,37 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Equations that `solve` rejects.
+//!
+//! The JSON tests only compare successful outputs, so the errors are tested here.
+
+use imp_backend::{Callbacks, Interpreter};
+
+/// Evaluates each test in a new interpreter and compares its error message to the expected text.
+fn check(tests: &[(&str, &str)]) {
+    for (test, expected) in tests {
+        let mut interp = Interpreter::builder().build();
+        match interp.process(test, Callbacks::default()) {
+            Ok(_) => panic!("`{}` did not fail", test),
+            Err(e) => assert_eq!(e.inner.to_string(), *expected, "`{}`", test),
+        }
+    }
+}
+
+#[test]
+fn no_solutions() {
+    check(&[
+        ("solve x (= x (+ x 1))", "domain error: the equation has no solutions"),
+        ("solve x (= (/ 1 x) 0)", "domain error: the equation has no solutions"),
+        ("solve x (= (/ 2 (pow x 2)) 0)", "domain error: the equation has no solutions"),
+    ]);
+}
+
+#[test]
+fn degree_too_high() {
+    check(&[
+        ("solve x (= (pow x 100) 1)", "cannot solve equations of degree greater than 64"),
+        ("solve x (= (* (pow x 40) (pow x 40)) 1)", "cannot solve equations of degree greater than 64"),
+    ]);
+}
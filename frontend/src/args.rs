@@ -7,6 +7,8 @@ use std::fmt;
 #[derive(Default)]
 pub struct Args {
     pub script_filepath: Option<String>,
+    /// Code to evaluate instead of starting the shell.
+    pub eval_code: Option<String>,
     pub config_filepath: Option<String>,
     pub should_print_vers: bool,
 }
@@ -30,6 +32,9 @@ impl Args {
                 "i" | "in" => {
                     args.script_filepath = Some(setting.value);
                 }
+                "e" | "eval" => {
+                    args.eval_code = Some(setting.value);
+                }
                 "c" | "config" => {
                     args.config_filepath = Some(setting.value);
                 }
@@ -51,11 +56,24 @@ struct Setting {
     value: String,
 }
 
+/// The names of settings that are flags, which take no value.
+const FLAGS: &[&str] = &["V", "version"];
+
 impl Setting {
     fn parse(args: &mut impl Iterator<Item = String>) -> Result<Setting, Error> {
         let first = args.next().ok_or(Error::ExpectedSetting)?;
-        let (_, name) = first.split_once('-').ok_or(Error::ExpectedSettingName)?;
-        let value = args.next().ok_or(Error::ExpectedSettingValue)?;
+        // Settings are prefixed by one dash in their short form (e.g., `-i`) or two in their long
+        // form (e.g., `--in`).
+        let name = first
+            .strip_prefix("--")
+            .or_else(|| first.strip_prefix('-'))
+            .filter(|it| !it.is_empty())
+            .ok_or(Error::ExpectedSettingName)?;
+        let value = if FLAGS.contains(&name) {
+            String::new()
+        } else {
+            args.next().ok_or(Error::ExpectedSettingValue)?
+        };
 
         Ok(Setting {
             name: name.to_string(),
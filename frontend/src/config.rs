@@ -2,12 +2,19 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
+use imp_backend::evaluator::env::MAX_CALL_DEPTH;
 use serde::{Deserialize, Serialize};
-use std::{fmt, fs, path::Path};
+use std::{fmt, fs, path::{Path, PathBuf}};
 
 #[derive(Deserialize, Serialize)]
 pub struct Config {
+    #[serde(default)]
+    pub eval: Eval,
+    #[serde(default)]
+    pub history: History,
     pub output: Output,
+    #[serde(default)]
+    pub plot: Plot,
     pub prompt: Prompt,
     pub spans: Spans,
 }
@@ -15,9 +22,12 @@ pub struct Config {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            eval: Eval::default(),
+            history: History::default(),
             output: Output {
                 color: ansi_term::Color::Yellow,
             },
+            plot: Plot::default(),
             prompt: Prompt {
                 color: ansi_term::Color::Green,
                 padding: 1
@@ -34,6 +44,10 @@ impl Config {
         let buf = fs::read(path).map_err(Error::Io)?;
         let config: Config = toml::from_slice(buf.as_slice()).map_err(Error::Toml)?;
 
+        if config.eval.max_call_depth > MAX_CALL_DEPTH {
+            return Err(Error::MaxCallDepth(config.eval.max_call_depth));
+        }
+
         Ok(config)
     }
 }
@@ -41,6 +55,8 @@ impl Config {
 pub enum Error {
     Io(std::io::Error),
     Toml(toml::de::Error),
+    /// The maximum call depth exceeds [`MAX_CALL_DEPTH`].
+    MaxCallDepth(usize),
 }
 
 impl fmt::Display for Error {
@@ -51,16 +67,133 @@ impl fmt::Display for Error {
             match self {
                 Self::Io(e) => e.to_string(),
                 Self::Toml(e) => e.to_string(),
+                Self::MaxCallDepth(it) => format!(
+                    "eval.max_call_depth is {}, but may be at most {}",
+                    it, MAX_CALL_DEPTH,
+                ),
             },
         )
     }
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct Eval {
+    /// The unit in which trigonometric operations measure angles.
+    pub angle_mode: AngleMode,
+    /// The maximum depth of nested calls to user-defined functions, which may be at most
+    /// [`MAX_CALL_DEPTH`].
+    pub max_call_depth: usize,
+}
+
+impl Default for Eval {
+    fn default() -> Self {
+        Self {
+            angle_mode: AngleMode::Radians,
+            max_call_depth: imp_backend::evaluator::env::DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AngleMode {
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+impl AngleMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Radians => "radians",
+            Self::Degrees => "degrees",
+            Self::Gradians => "gradians",
+        }
+    }
+}
+
+impl From<AngleMode> for imp_backend::evaluator::env::AngleMode {
+    fn from(it: AngleMode) -> Self {
+        match it {
+            AngleMode::Radians => Self::Radians,
+            AngleMode::Degrees => Self::Degrees,
+            AngleMode::Gradians => Self::Gradians,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct History {
+    /// The file to which shell input is saved, which defaults to `imp/history` in the user's data
+    /// directory.
+    pub file: Option<PathBuf>,
+    /// The maximum number of lines of input that are remembered. History is disabled if this is
+    /// zero.
+    pub size: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self { file: None, size: 1000 }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Output {
     pub color: ansi_term::Color,
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct Plot {
+    /// The directory to which graphs are written, which defaults to the system's temporary
+    /// directory.
+    pub dir: Option<PathBuf>,
+    /// The width, in pixels, of graph images.
+    pub width: u32,
+    /// The height, in pixels, of graph images.
+    pub height: u32,
+    /// Determines if graphs are also drawn in the terminal.
+    pub terminal: bool,
+    /// Determines if graphs are written to SVG and PNG files. This only takes effect if graphs are
+    /// drawn in the terminal, as they would not otherwise be output at all.
+    pub files: bool,
+    /// The width, in columns, of graphs drawn in the terminal, which defaults to the width of the
+    /// terminal.
+    pub term_width: Option<usize>,
+    /// The height, in lines, of graphs drawn in the terminal.
+    pub term_rows: usize,
+    /// The colors of successive series in graphs drawn in the terminal.
+    pub colors: Vec<ansi_term::Color>,
+}
+
+impl Default for Plot {
+    fn default() -> Self {
+        use ansi_term::Color;
+
+        Self {
+            dir: None,
+            width: 640,
+            height: 480,
+            terminal: true,
+            files: true,
+            term_width: None,
+            term_rows: 12,
+            colors: vec![Color::Blue, Color::Red, Color::Green, Color::Yellow, Color::Purple, Color::Cyan],
+        }
+    }
+}
+
+impl Plot {
+    /// Determines if graphs are written to files, which they always are if they are not drawn in
+    /// the terminal.
+    pub fn writes_files(&self) -> bool {
+        self.files || !self.terminal
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct Prompt {
     pub color: ansi_term::Color,
@@ -7,6 +7,7 @@ use std::fmt;
 pub enum FrontendError {
     Args(crate::args::Error),
     Config(crate::config::Error),
+    Script(crate::script::Error),
 }
 
 impl fmt::Display for FrontendError {
@@ -22,10 +23,12 @@ impl fmt::Display for FrontendError {
             match self {
                 Self::Args(_) => "args",
                 Self::Config(_) => "config",
+                Self::Script(e) => e.stage(),
             },
             match self {
                 Self::Args(e) => e.to_string(),
                 Self::Config(e) => e.to_string(),
+                Self::Script(e) => e.to_string(),
             }
         )
     }
@@ -36,6 +39,7 @@ impl FrontendError {
         match self {
             Self::Args(_) => 1,
             Self::Config(_) => 2,
+            Self::Script(e) => e.exit_code(),
         }
     }
 }
@@ -61,6 +65,24 @@ impl fmt::Display for BackendError {
     }
 }
 
+/// Supplementary information attached to an error.
+pub struct Note<T: fmt::Display>(pub T);
+
+impl<T: fmt::Display> fmt::Display for Note<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            crate::color(
+                supports_color::Stream::Stderr,
+                "note".to_string(),
+                ansi_term::Style::new().fg(ansi_term::Color::Blue),
+            ),
+            self.0,
+        )
+    }
+}
+
 pub enum Stage {
     Lexer,
     Parser,
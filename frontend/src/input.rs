@@ -0,0 +1,45 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Input that spans multiple lines.
+//!
+//! An expression may be split over several lines, which are accumulated until every parenthesis
+//! and brace has been closed and then evaluated together, joined by newlines. Spans then index
+//! characters within the accumulated input rather than within any one line.
+
+use imp_backend::lexer::{self, Token};
+
+/// Determines if IMPL code contains parentheses or braces that have yet to be closed, in which case
+/// more lines are expected.
+///
+/// Code that fails to lex is considered complete so that the error may be reported.
+pub fn is_incomplete(code: &str) -> bool {
+    let Ok(output) = lexer::lex(code) else {
+        return false;
+    };
+
+    let depth = output.tokens.iter().fold(0isize, |depth, token| match token.inner {
+        Token::LParen | Token::LBrace => depth + 1,
+        Token::RParen | Token::RBrace => depth - 1,
+        _ => depth,
+    });
+
+    depth > 0
+}
+
+/// Finds the zero-based line and column of the character at the given index.
+pub fn locate(code: &str, idx: usize) -> (usize, usize) {
+    let mut line = 0;
+    let mut col = 0;
+    for ch in code.chars().take(idx) {
+        if ch == '\n' {
+            line += 1;
+            col = 0;
+        } else {
+            col += 1;
+        }
+    }
+
+    (line, col)
+}
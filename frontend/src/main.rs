@@ -5,8 +5,17 @@
 //! Interactive Mathematical Processor (IMP).
 //!
 //! This is the reference implementation of an [IMP] frontend. It interprets IMP expressions
-//! line-by-line in a shell-like, command-line interface. IMP is stateless; however, configuration
-//! is possible through a TOML configuration file.
+//! line-by-line in a shell-like, command-line interface. Bindings made with `let` persist from one
+//! line to the next, as do results, which `$` and `$n` refer to, and configuration is possible
+//! through a TOML configuration file. Input is read with a line editor whose history is saved
+//! between sessions; Ctrl-C discards the current line, and Ctrl-D exits. Tab completes operation
+//! and binding names, shell commands, and their arguments, and the signature of an operation is
+//! hinted once its name has been entered. Expressions with unclosed parentheses or braces continue
+//! onto following lines, which are prompted with `.`.
+//!
+//! Graphs produced by `plot` are written as SVG and PNG files to a temporary directory, or to the
+//! directory given in the configuration file, and their paths are printed. They are also drawn in
+//! the terminal; either form of output may be disabled in the configuration file, though not both.
 //!
 //! By default, output is colored with ANSI color codes if IMP determines that the containing
 //! terminal supports them (see the [supports-color] crate). Interpreter errors are visualized with
@@ -25,12 +34,15 @@
 mod args;
 mod config;
 mod err;
+mod input;
+mod plot;
+mod script;
 mod shell;
 
 use args::Args;
 use config::Config;
 use shell::Shell;
-use std::process::ExitCode;
+use std::{io::{self, IsTerminal as _}, process::ExitCode};
 
 fn main() -> ExitCode {
     if let Err(e) = main_impl() {
@@ -66,10 +78,33 @@ fn main_impl() -> Result<(), err::FrontendError> {
             Ok(Config::default())
         })?;
 
-    let shell = Shell::new(config);
-    loop {
-        shell.interpret_line();
-    }
+    let result = if let Some(code) = args.eval_code {
+        script::run_code(code.as_str(), &config)
+    } else if let Some(path) = args.script_filepath {
+        script::run_file(path.as_str(), &config)
+    } else if !io::stdin().is_terminal() {
+        // Input is piped, so there is nobody to prompt.
+        script::run_stdin(&config)
+    } else {
+        let mut shell = Shell::new(config);
+        while shell.interpret_line() {}
+        if let Err(e) = shell.save_history() {
+            // History is a convenience, so failing to save it is not fatal.
+            eprintln!("{}", err::Note(format!("failed to save history: {}", e)));
+        }
+
+        Ok(())
+    };
+
+    result.map_err(err::FrontendError::Script)
+}
+
+/// Creates an interpreter configured by the `eval` section of the configuration.
+fn interpreter(config: &Config) -> imp_backend::Interpreter {
+    imp_backend::Interpreter::builder()
+        .angle_mode(config.eval.angle_mode.into())
+        .max_call_depth(config.eval.max_call_depth)
+        .build()
 }
 
 fn print_version() {
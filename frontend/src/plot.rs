@@ -0,0 +1,105 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Rendering of graphs to image files and the terminal.
+
+mod png;
+mod svg;
+pub mod term;
+
+use imp_backend::evaluator::graphic::Graphic;
+use std::{fs, io, path::PathBuf};
+
+/// The colors of successive series, as RGB triples.
+const PALETTE: [(u8, u8, u8); 6] = [
+    (0x1f, 0x77, 0xb4),
+    (0xd6, 0x27, 0x28),
+    (0x2c, 0xa0, 0x2c),
+    (0xff, 0x7f, 0x0e),
+    (0x94, 0x67, 0xbd),
+    (0x8c, 0x56, 0x4b),
+];
+
+/// The space, in pixels, between the edges of an image and the plot area.
+const MARGIN: f64 = 48.0;
+
+/// Renders a graph to SVG and PNG files named after `stem` in the configured directory, returning
+/// their paths.
+pub fn write(graphic: &Graphic, config: &crate::config::Plot, stem: &str) -> io::Result<(PathBuf, PathBuf)> {
+    let dir = config.dir.clone().unwrap_or_else(std::env::temp_dir);
+    fs::create_dir_all(&dir)?;
+
+    let frame = Frame::new(graphic, config.width, config.height);
+    let svg_path = dir.join(format!("{}.svg", stem));
+    let png_path = dir.join(format!("{}.png", stem));
+    fs::write(&svg_path, svg::render(&frame))?;
+    fs::write(&png_path, png::render(&frame))?;
+
+    Ok((svg_path, png_path))
+}
+
+/// The color of the series at the given index.
+fn color(idx: usize) -> (u8, u8, u8) {
+    PALETTE[idx % PALETTE.len()]
+}
+
+/// A graph placed within an image of a particular size.
+struct Frame<'a> {
+    graphic: &'a Graphic,
+    width: u32,
+    height: u32,
+}
+
+impl<'a> Frame<'a> {
+    fn new(graphic: &'a Graphic, width: u32, height: u32) -> Self {
+        // The plot area must not vanish within the margins.
+        let min = (3.0 * MARGIN) as u32;
+
+        Self { graphic, width: width.max(min), height: height.max(min) }
+    }
+
+    /// The left, top, right, and bottom edges of the plot area, in pixels.
+    fn area(&self) -> (f64, f64, f64, f64) {
+        (MARGIN, MARGIN / 2.0, f64::from(self.width) - MARGIN / 2.0, f64::from(self.height) - MARGIN)
+    }
+
+    /// Maps a point of the graph to pixel coordinates.
+    fn to_px(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let (left, top, right, bottom) = self.area();
+        let (x_lo, x_hi) = self.graphic.x_range;
+        let (y_lo, y_hi) = self.graphic.y_range;
+
+        (
+            left + (x - x_lo) / (x_hi - x_lo) * (right - left),
+            bottom - (y - y_lo) / (y_hi - y_lo) * (bottom - top),
+        )
+    }
+
+    /// The lines along which the axes lie, in pixel coordinates, where they are within the graph.
+    fn axes(&self) -> Vec<((f64, f64), (f64, f64))> {
+        let (x_lo, x_hi) = self.graphic.x_range;
+        let (y_lo, y_hi) = self.graphic.y_range;
+        let mut axes = Vec::new();
+
+        if (x_lo..=x_hi).contains(&0.0) {
+            axes.push((self.to_px((0.0, y_lo)), self.to_px((0.0, y_hi))));
+        }
+        if (y_lo..=y_hi).contains(&0.0) {
+            axes.push((self.to_px((x_lo, 0.0)), self.to_px((x_hi, 0.0))));
+        }
+
+        axes
+    }
+}
+
+/// Formats an axis bound compactly.
+fn format_bound(val: f64) -> String {
+    let it = format!("{:.3}", val);
+    let it = it.trim_end_matches('0').trim_end_matches('.');
+
+    match it {
+        "-0" => "0".to_string(),
+        it => it.to_string(),
+    }
+}
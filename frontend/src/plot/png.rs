@@ -0,0 +1,160 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A minimal PNG renderer.
+//!
+//! Images are rasterized into an RGB buffer and encoded without compression, which keeps the
+//! encoder small at the cost of larger files. Text is not rendered; the SVG output carries labels.
+
+use super::{color, Frame};
+
+const WHITE: (u8, u8, u8) = (0xff, 0xff, 0xff);
+const BORDER: (u8, u8, u8) = (0x88, 0x88, 0x88);
+const AXIS: (u8, u8, u8) = (0xbb, 0xbb, 0xbb);
+
+pub fn render(frame: &Frame) -> Vec<u8> {
+    let mut canvas = Canvas::new(frame);
+    let (left, top, right, bottom) = frame.area();
+
+    canvas.line((left, top), (right, top), BORDER, false);
+    canvas.line((right, top), (right, bottom), BORDER, false);
+    canvas.line((right, bottom), (left, bottom), BORDER, false);
+    canvas.line((left, bottom), (left, top), BORDER, false);
+    for (a, b) in frame.axes() {
+        canvas.line(a, b, AXIS, false);
+    }
+
+    for (idx, series) in frame.graphic.series.iter().enumerate() {
+        for line in frame.graphic.polylines(series) {
+            for pair in line.windows(2) {
+                canvas.line(frame.to_px(pair[0]), frame.to_px(pair[1]), color(idx), true);
+            }
+        }
+    }
+
+    encode(&canvas)
+}
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    /// The plot area, outside of which series are clipped.
+    area: (f64, f64, f64, f64),
+    pixels: Vec<(u8, u8, u8)>,
+}
+
+impl Canvas {
+    fn new(frame: &Frame) -> Self {
+        let (width, height) = (frame.width as usize, frame.height as usize);
+
+        Self { width, height, area: frame.area(), pixels: vec![WHITE; width * height] }
+    }
+
+    /// Draws a line two pixels thick with the DDA algorithm.
+    fn line(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), color: (u8, u8, u8), clip: bool) {
+        let steps = (x2 - x1).abs().max((y2 - y1).abs()).ceil().max(1.0);
+        // Lines that leave the image entirely, as near poles, would otherwise take very many steps.
+        if !steps.is_finite() || steps > 8.0 * (self.width + self.height) as f64 {
+            return;
+        }
+
+        for step in 0..=(steps as usize) {
+            let t = step as f64 / steps;
+            let (x, y) = (x1 + t * (x2 - x1), y1 + t * (y2 - y1));
+            self.plot(x, y, color, clip);
+            self.plot(x + 1.0, y, color, clip);
+            self.plot(x, y + 1.0, color, clip);
+        }
+    }
+
+    fn plot(&mut self, x: f64, y: f64, color: (u8, u8, u8), clip: bool) {
+        let (left, top, right, bottom) = self.area;
+        if clip && !((left..=right).contains(&x) && (top..=bottom).contains(&y)) {
+            return;
+        }
+        if (x < 0.0) || (y < 0.0) {
+            return;
+        }
+
+        let (x, y) = (x as usize, y as usize);
+        if (x < self.width) && (y < self.height) {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+}
+
+fn encode(canvas: &Canvas) -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+
+    let mut header = Vec::new();
+    header.extend((canvas.width as u32).to_be_bytes());
+    header.extend((canvas.height as u32).to_be_bytes());
+    // 8-bit RGB, with the default compression, filter, and interlace methods.
+    header.extend([8, 2, 0, 0, 0]);
+    write_chunk(&mut png, b"IHDR", &header);
+
+    // Each scanline is preceded by its filter type, which is always "none".
+    let mut raw = Vec::with_capacity(canvas.height * (1 + 3 * canvas.width));
+    for row in canvas.pixels.chunks(canvas.width) {
+        raw.push(0);
+        raw.extend(row.iter().flat_map(|(r, g, b)| [*r, *g, *b]));
+    }
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// Wraps data in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // The header specifies deflate with a 32 KiB window and no preset dictionary.
+    let mut out = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(u8::from(is_final));
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
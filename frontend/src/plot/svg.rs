@@ -0,0 +1,101 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+use super::{color, format_bound, Frame};
+use std::fmt::Write as _;
+
+pub fn render(frame: &Frame) -> String {
+    let (left, top, right, bottom) = frame.area();
+    let graphic = frame.graphic;
+    let mut svg = String::new();
+
+    // Writing to a `String` cannot fail, so the results of `write!` are ignored throughout.
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="sans-serif" font-size="12">"#,
+        frame.width,
+        frame.height,
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+    let _ = writeln!(
+        svg,
+        r#"<clipPath id="area"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+        left,
+        top,
+        right - left,
+        bottom - top,
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#888"/>"##,
+        left,
+        top,
+        right - left,
+        bottom - top,
+    );
+
+    for ((x1, y1), (x2, y2)) in frame.axes() {
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="#bbb"/>"##,
+            x1, y1, x2, y2,
+        );
+    }
+
+    // Bounds of the axes.
+    let (x_lo, x_hi) = graphic.x_range;
+    let (y_lo, y_hi) = graphic.y_range;
+    let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="start">{}</text>"#, left, bottom + 16.0, format_bound(x_lo));
+    let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#, right, bottom + 16.0, format_bound(x_hi));
+    let _ = writeln!(
+        svg,
+        r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+        (left + right) / 2.0,
+        bottom + 32.0,
+        escape(graphic.var.as_str()),
+    );
+    let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#, left - 4.0, bottom, format_bound(y_lo));
+    let _ = writeln!(svg, r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#, left - 4.0, top + 12.0, format_bound(y_hi));
+
+    for (idx, series) in graphic.series.iter().enumerate() {
+        let (r, g, b) = color(idx);
+
+        for line in graphic.polylines(series) {
+            let points: Vec<_> = line
+                .into_iter()
+                .map(|it| {
+                    let (x, y) = frame.to_px(it);
+                    format!("{:.2},{:.2}", x, y)
+                })
+                .collect();
+            let _ = writeln!(
+                svg,
+                r##"<polyline clip-path="url(#area)" fill="none" stroke="#{:02x}{:02x}{:02x}" stroke-width="1.5" points="{}"/>"##,
+                r,
+                g,
+                b,
+                points.join(" "),
+            );
+        }
+
+        // The legend.
+        let _ = writeln!(
+            svg,
+            r##"<text x="{}" y="{}" fill="#{:02x}{:02x}{:02x}">{}</text>"##,
+            left + 8.0,
+            top + 16.0 * (idx + 1) as f64,
+            r,
+            g,
+            b,
+            escape(series.label.as_str()),
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,171 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Rendering of graphs to the terminal with Unicode braille characters.
+//!
+//! Each character cell holds a 2×4 grid of dots, so a graph has twice the horizontal and four
+//! times the vertical resolution of the terminal.
+
+use super::format_bound;
+use crate::config::Plot;
+use imp_backend::evaluator::graphic::Graphic;
+
+/// The width assumed when that of the terminal cannot be determined.
+const DEFAULT_WIDTH: usize = 80;
+
+/// The bits of the dots of a braille character, indexed by row and then column.
+const DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+pub fn render(graphic: &Graphic, config: &Plot) -> String {
+    let (y_lo, y_hi) = graphic.y_range;
+    let labels = [format_bound(y_hi), format_bound(y_lo)];
+    let gutter = labels.iter().map(String::len).max().unwrap_or(0) + 1;
+
+    let width = config.term_width.or_else(terminal_width).unwrap_or(DEFAULT_WIDTH);
+    let cols = width.saturating_sub(gutter + 1).max(8);
+    let rows = config.term_rows.max(2);
+    let mut canvas = Canvas::new(cols, rows);
+
+    // Axes are drawn first so that series are drawn over them.
+    let (x_lo, x_hi) = graphic.x_range;
+    let to_dot = |(x, y): (f64, f64)| {
+        (
+            (x - x_lo) / (x_hi - x_lo) * (2 * cols - 1) as f64,
+            (y_hi - y) / (y_hi - y_lo) * (4 * rows - 1) as f64,
+        )
+    };
+    if (x_lo..=x_hi).contains(&0.0) {
+        canvas.line(to_dot((0.0, y_lo)), to_dot((0.0, y_hi)), None);
+    }
+    if (y_lo..=y_hi).contains(&0.0) {
+        canvas.line(to_dot((x_lo, 0.0)), to_dot((x_hi, 0.0)), None);
+    }
+
+    for (idx, series) in graphic.series.iter().enumerate() {
+        for line in graphic.polylines(series) {
+            if let [point] = line.as_slice() {
+                canvas.line(to_dot(*point), to_dot(*point), Some(idx));
+            }
+            for pair in line.windows(2) {
+                canvas.line(to_dot(pair[0]), to_dot(pair[1]), Some(idx));
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    for (row, cells) in canvas.cells.chunks(cols).enumerate() {
+        let label = match row {
+            0 => labels[0].as_str(),
+            _ if row == rows - 1 => labels[1].as_str(),
+            _ => "",
+        };
+        let tick = if label.is_empty() { '│' } else { '┤' };
+
+        let mut line = format!("{:>2$}{}", label, tick, gutter);
+        for (bits, series) in cells {
+            let ch = char::from_u32(0x2800 + u32::from(*bits)).unwrap_or(' ');
+            line.push_str(paint(config, ch.to_string(), *series).as_str());
+        }
+        out.push(line);
+    }
+
+    out.push(format!("{:>2$}└{}", "", "─".repeat(cols), gutter));
+    let (lo, hi) = (format_bound(x_lo), format_bound(x_hi));
+    let var = graphic.var.as_str();
+    let space = cols.saturating_sub(lo.len() + var.len() + hi.len());
+    out.push(format!(
+        "{:>w$} {}{:l$}{}{:r$}{}",
+        "",
+        lo,
+        "",
+        var,
+        "",
+        hi,
+        w = gutter,
+        l = space / 2,
+        r = space - space / 2,
+    ));
+
+    // The legend.
+    for (idx, series) in graphic.series.iter().enumerate() {
+        out.push(format!("{:>2$} {}", "", paint(config, format!("━ {}", series.label), Some(idx)), gutter));
+    }
+
+    out.join("\n")
+}
+
+/// Colors text with the color of a series, if any.
+fn paint(config: &Plot, text: String, series: Option<usize>) -> String {
+    match series.filter(|_| !config.colors.is_empty()) {
+        Some(idx) => crate::color(
+            supports_color::Stream::Stdout,
+            text,
+            ansi_term::Style::new().fg(config.colors[idx % config.colors.len()]),
+        ),
+        None => text,
+    }
+}
+
+struct Canvas {
+    cols: usize,
+    rows: usize,
+    /// The dots of each character cell and the series that last drew in it, if any.
+    cells: Vec<(u8, Option<usize>)>,
+}
+
+impl Canvas {
+    fn new(cols: usize, rows: usize) -> Self {
+        Self { cols, rows, cells: vec![(0, None); cols * rows] }
+    }
+
+    /// Draws a line between two points in dot coordinates.
+    fn line(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), series: Option<usize>) {
+        let steps = (x2 - x1).abs().max((y2 - y1).abs()).ceil().max(1.0);
+        // Lines that leave the canvas entirely, as near poles, would otherwise take very many steps.
+        if !steps.is_finite() || steps > 8.0 * (self.cols + self.rows) as f64 {
+            return;
+        }
+
+        for step in 0..=(steps as usize) {
+            let t = step as f64 / steps;
+            self.dot((x1 + t * (x2 - x1)).round(), (y1 + t * (y2 - y1)).round(), series);
+        }
+    }
+
+    fn dot(&mut self, x: f64, y: f64, series: Option<usize>) {
+        if (x < 0.0) || (y < 0.0) {
+            return;
+        }
+
+        let (x, y) = (x as usize, y as usize);
+        if (x >= 2 * self.cols) || (y >= 4 * self.rows) {
+            return;
+        }
+
+        let cell = &mut self.cells[(y / 4) * self.cols + (x / 2)];
+        cell.0 |= DOTS[y % 4][x % 2];
+        if series.is_some() {
+            cell.1 = series;
+        }
+    }
+}
+
+/// Determines the width, in columns, of the terminal.
+fn terminal_width() -> Option<usize> {
+    terminal_width_impl().or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+}
+
+#[cfg(unix)]
+fn terminal_width_impl() -> Option<usize> {
+    // SAFETY: `TIOCGWINSZ` only writes a `winsize` to the given pointer, which is valid.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+
+    ((result == 0) && (size.ws_col > 0)).then_some(usize::from(size.ws_col))
+}
+
+#[cfg(not(unix))]
+fn terminal_width_impl() -> Option<usize> {
+    None
+}
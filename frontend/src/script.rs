@@ -0,0 +1,144 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Non-interactive execution of IMPL code.
+//!
+//! Code from script files, the `-e` setting, or piped standard input is evaluated line-by-line in
+//! a single interpreter, just as if it were entered into the shell, and the result of each line is
+//! printed without prompts. As in the shell, an expression may span several lines as long as its
+//! parentheses are unclosed. Evaluation stops at the first error.
+
+use crate::config::Config;
+use imp_backend::{evaluator::Output, span::Span, Callbacks};
+use std::{fmt, fs, io::{self, BufRead as _}};
+
+/// Evaluates a script file.
+pub fn run_file(path: &str, config: &Config) -> Result<(), Error> {
+    let code = fs::read_to_string(path).map_err(|inner| Error::Read { path: path.to_string(), inner })?;
+
+    run(path, code.lines().map(|it| Ok(it.to_string())), config)
+}
+
+/// Evaluates code given on the command line.
+pub fn run_code(code: &str, config: &Config) -> Result<(), Error> {
+    run("<eval>", code.lines().map(|it| Ok(it.to_string())), config)
+}
+
+/// Evaluates standard input until EOF.
+pub fn run_stdin(config: &Config) -> Result<(), Error> {
+    run("<stdin>", io::stdin().lock().lines(), config)
+}
+
+/// Evaluates lines of code from a source of the given name, which is used in error messages.
+fn run(name: &str, lines: impl Iterator<Item = io::Result<String>>, config: &Config) -> Result<(), Error> {
+    let mut interp = crate::interpreter(config);
+    let mut plot_count = 0;
+
+    let read_err = |inner| Error::Read { path: name.to_string(), inner };
+
+    let mut lines = lines.enumerate();
+    while let Some((idx, line)) = lines.next() {
+        let mut code = line.map_err(read_err)?;
+        // Continue reading until every expression has been closed.
+        while crate::input::is_incomplete(code.as_str()) {
+            let Some((_, line)) = lines.next() else {
+                break;
+            };
+            code.push('\n');
+            code.push_str(line.map_err(read_err)?.as_str());
+        }
+
+        let outputs = interp.process(code.as_str(), Callbacks::default()).map_err(|e| Error::Eval {
+            path: name.to_string(),
+            line: idx + 1,
+            code: code.clone(),
+            inner: Box::new(e),
+        })?;
+
+        for output in outputs {
+            match output {
+                Output::Text(text) => {
+                    println!("{}", text);
+                }
+                Output::Graphic(graphic) => {
+                    if config.plot.writes_files() {
+                        plot_count += 1;
+                        let stem = format!("imp-{}-{}", std::process::id(), plot_count);
+                        let (svg, png) = crate::plot::write(&graphic, &config.plot, stem.as_str()).map_err(Error::Plot)?;
+                        println!("{}, {}", svg.display(), png.display());
+                    }
+
+                    if config.plot.terminal {
+                        println!("{}", crate::plot::term::render(&graphic, &config.plot));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub enum Error {
+    /// The code could not be read.
+    Read { path: String, inner: io::Error },
+    /// A graph could not be written.
+    Plot(io::Error),
+    /// A line of code failed to evaluate.
+    Eval {
+        path: String,
+        /// The one-based number of the line at which the code begins.
+        line: usize,
+        /// The code, which may span several lines.
+        code: String,
+        inner: Box<Span<imp_backend::Error>>,
+    },
+}
+
+impl Error {
+    /// A short description of where the error occurred.
+    pub fn stage(&self) -> &'static str {
+        match self {
+            Self::Read { .. } | Self::Plot(_) => "script",
+            Self::Eval { .. } => "eval",
+        }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Read { .. } | Self::Plot(_) => 3,
+            Self::Eval { .. } => 4,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read { path, inner } => {
+                write!(f, "{}: {}", path, inner)
+            }
+            Self::Plot(e) => {
+                write!(f, "failed to write graph: {}", e)
+            }
+            Self::Eval { path, line, code, inner } => {
+                // Spans index characters within the code; columns are one-based.
+                let location = |range: &std::ops::Range<usize>| {
+                    let (line_offset, col) = crate::input::locate(code, range.start);
+                    format!("{}:{}:{}", path, line + line_offset, col + 1)
+                };
+                write!(f, "{}: {}", location(&inner.range), inner.inner)?;
+
+                // The expressions that enclosed the erroneous one, from innermost to outermost.
+                if let imp_backend::Error::Evaluator(e) = &inner.inner {
+                    for frame in e.backtrace() {
+                        write!(f, "\n{}: {}", location(&frame.range), crate::err::Note(&frame.inner))?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
@@ -3,42 +3,123 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 mod cmd;
+mod editor;
 mod imp;
 
 use crate::config::Config;
-use std::io::{self, Write as _};
+use imp_backend::Interpreter;
+use rustyline::{error::ReadlineError, CompletionType, Editor};
+use std::path::PathBuf;
 
 pub struct Shell {
     config: Config,
+    /// The interpreter, which holds the bindings made by all previous lines.
+    interp: Interpreter,
+    /// The line editor, which holds the history of user input.
+    editor: Editor<editor::Helper>,
+    /// The number of graphs written so far, which distinguishes their file names.
+    plot_count: usize,
 }
 
 impl Shell {
     pub fn new(config: Config) -> Self {
-        Self { config }
+        let interp = crate::interpreter(&config);
+        let mut editor = Editor::with_config(
+            rustyline::Config::builder()
+                .max_history_size(config.history.size)
+                .history_ignore_dups(true)
+                .history_ignore_space(true)
+                .completion_type(CompletionType::List)
+                .build(),
+        );
+        editor.set_helper(Some(editor::Helper::new(&interp)));
+        if let Some(path) = history_path(&config) {
+            // There is no history to load the first time the shell is run.
+            let _ = editor.load_history(&path);
+        }
+
+        Self { config, interp, editor, plot_count: 0 }
     }
 
     /// Prints the shell prompt, reads user input, and executes the appropriate processor function.
-    pub fn interpret_line(&self) {
-        self.print_prompt();
-        let user_input = Self::read_user_input();
+    ///
+    /// Returns `false` once input has ended.
+    pub fn interpret_line(&mut self) -> bool {
+        let mut user_input = match self.read_line(self.prompt('>').as_str()) {
+            Read::Line(it) => it,
+            Read::Interrupted => return true,
+            Read::End => return false,
+        };
+
+        // Continue reading until every expression has been closed.
+        while !cmd::is_cmd(user_input.as_str()) && crate::input::is_incomplete(user_input.as_str()) {
+            match self.read_line(self.prompt('.').as_str()) {
+                Read::Line(it) => {
+                    user_input.push('\n');
+                    user_input.push_str(it.as_str());
+                }
+                Read::Interrupted => return true,
+                // The incomplete input is evaluated so that the missing parenthesis is reported.
+                Read::End => break,
+            }
+        }
+
+        if !user_input.trim().is_empty() {
+            self.editor.add_history_entry(user_input.as_str());
+        }
 
         if cmd::is_cmd(user_input.as_str()) {
             cmd::process(self, user_input.as_str());
         } else {
             imp::process(self, user_input.as_str())
         }
+
+        // The input may have defined new names to complete.
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.refresh(&self.interp);
+        }
+
+        true
     }
 
-    fn print_prompt(&self) {
-        print!(
+    /// Writes the history of user input to the history file, if there is one.
+    pub fn save_history(&mut self) -> Result<(), ReadlineError> {
+        let Some(path) = history_path(&self.config) else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        self.editor.save_history(&path)
+    }
+
+    fn read_line(&mut self, prompt: &str) -> Read {
+        match self.editor.readline(prompt) {
+            Ok(it) => Read::Line(it),
+            // Ctrl-C discards the current input.
+            Err(ReadlineError::Interrupted) => Read::Interrupted,
+            // Ctrl-D ends input.
+            Err(ReadlineError::Eof) => Read::End,
+            Err(e) => {
+                eprintln!("failed to read input: {}", e);
+                Read::End
+            }
+        }
+    }
+
+    /// Creates a prompt from the given symbol, which is `>` for new input and `.` for the
+    /// continuation of incomplete input.
+    fn prompt(&self, symbol: char) -> String {
+        format!(
             "{}{}",
             crate::color(
                 supports_color::Stream::Stdout,
-                ">".to_string(),
+                symbol.to_string(),
                 ansi_term::Style::new().bold().fg(self.config.prompt.color),
             ),
             self.prompt_padding(),
-        );
+        )
     }
 
     fn prompt_padding(&self) -> String {
@@ -46,13 +127,29 @@ impl Shell {
             .take(self.config.prompt.padding)
             .collect()
     }
+}
 
-    fn read_user_input() -> String {
-        let _ = io::stdout().lock().flush();
-        let mut input = String::new();
-        // TODO: Handle error.
-        let _ = io::stdin().read_line(&mut input);
+/// The result of reading a line of user input.
+enum Read {
+    Line(String),
+    /// The user pressed Ctrl-C.
+    Interrupted,
+    /// Input has ended.
+    End,
+}
 
-        input
+/// The path of the history file, which is `imp/history` within the user's data directory unless
+/// configured otherwise.
+///
+/// History is not saved if it is disabled or if the data directory cannot be determined.
+fn history_path(config: &Config) -> Option<PathBuf> {
+    if config.history.size == 0 {
+        return None;
     }
+
+    config
+        .history
+        .file
+        .clone()
+        .or_else(|| Some(dirs::data_dir()?.join("imp").join("history")))
 }
@@ -3,14 +3,18 @@
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
 use super::Shell;
+use crate::config::AngleMode;
+
+/// The long names of all commands.
+pub const NAMES: &[&str] = &["help", "aliases", "config", "angle"];
 
 /// Determines if user input is a shell command.
 pub fn is_cmd(it: &str) -> bool {
     it.starts_with(':')
 }
 
-pub fn process<'a>(this: &Shell, cmd: &'a str) {
-    let (name, _) = split(cmd);
+pub fn process<'a>(this: &mut Shell, cmd: &'a str) {
+    let (name, args) = split(cmd);
     match name {
         "h" | "help" => {
             print_usage();
@@ -21,6 +25,9 @@ pub fn process<'a>(this: &Shell, cmd: &'a str) {
         "c" | "config" => {
             print_config(this);
         }
+        "m" | "angle" => {
+            angle_mode(this, args.as_slice());
+        }
         // TODO: Add moar commands!
         // TODO: Handle invalid commands.
         _ => {}
@@ -45,14 +52,37 @@ fn split(cmd: &str) -> (&str, Vec<&str>) {
 fn print_usage() {
     println!("Commands:");
     println!("  :h, :help               Prints this usage information.");
-    println!("  :a, :aliases            Prints all defined aliases.");
+    println!("  :a, :aliases            Prints the operators that stand for operations.");
     println!("  :c, :config             Prints the current configuration.");
+    println!("  :m, :angle [rad|deg|grad]");
+    println!("                          Prints or sets the unit of angles.");
 }
 
 fn print_interp_aliases() {
-    todo!()
+    for (alias, name) in imp_backend::lexer::ALIASES {
+        println!("{}  {}", alias, name);
+    }
 }
 
 fn print_config(this: &Shell) {
     println!("{}", toml::to_string(&this.config).unwrap());
 }
+
+fn angle_mode(this: &mut Shell, args: &[&str]) {
+    let mode = match args {
+        [] => {
+            println!("{}", this.config.eval.angle_mode.name());
+            return;
+        }
+        ["rad"] => AngleMode::Radians,
+        ["deg"] => AngleMode::Degrees,
+        ["grad"] => AngleMode::Gradians,
+        _ => {
+            eprintln!("usage: :angle [rad|deg|grad]");
+            return;
+        }
+    };
+
+    this.config.eval.angle_mode = mode;
+    this.interp.env_mut().set_angle_mode(mode.into());
+}
@@ -0,0 +1,180 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Completion and hints for the line editor.
+//!
+//! Operation, function, and binding names are completed in IMPL code, and command names, as well as
+//! the arguments of some commands, are completed in shell commands. The signature of an operation
+//! is hinted once its name has been entered.
+
+use super::cmd;
+use imp_backend::Interpreter;
+use rustyline::{
+    completion::{Completer, Pair},
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+    Context,
+};
+use std::{borrow::Cow, collections::BTreeMap};
+
+/// The characters that separate names in IMPL code.
+const DELIMITERS: &[char] = &['(', ')', '{', '}', '$', '#', '^', '"'];
+
+pub struct Helper {
+    /// The signatures of all operations and user-defined functions, by name.
+    sigs: BTreeMap<String, String>,
+    /// The names of all global bindings.
+    bindings: Vec<String>,
+}
+
+impl Helper {
+    pub fn new(interp: &Interpreter) -> Self {
+        let mut it = Self {
+            sigs: BTreeMap::new(),
+            bindings: Vec::new(),
+        };
+        it.refresh(interp);
+
+        it
+    }
+
+    /// Refreshes the names known to the interpreter, which may change with each line of input.
+    pub fn refresh(&mut self, interp: &Interpreter) {
+        let env = interp.env();
+
+        let operations = env
+            .operation_names()
+            .filter_map(|name| Some((name.to_string(), env.operation_sig(name)?.to_string())));
+        let functions = env
+            .function_names()
+            .filter_map(|name| Some((name.to_string(), env.function_params(name)?.join(" "))));
+        self.sigs = operations.chain(functions).collect();
+
+        self.bindings = env.names().map(str::to_string).collect();
+        self.bindings.sort_unstable();
+    }
+
+    fn complete_code(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let start = word_start(line, pos);
+        let prefix = &line[start..pos];
+
+        let candidates = self
+            .sigs
+            .keys()
+            .chain(self.bindings.iter())
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| Pair { display: name.clone(), replacement: name.clone() })
+            .collect();
+
+        (start, candidates)
+    }
+
+    fn complete_cmd(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        // The command name begins after the colon.
+        let name_start = line.find(':').map_or(0, |idx| idx + 1);
+        let Some((name, _)) = line[name_start..pos].split_once(char::is_whitespace) else {
+            let prefix = &line[name_start..pos];
+            let candidates = cmd::NAMES
+                .iter()
+                .filter(|name| name.starts_with(prefix))
+                .map(|name| Pair { display: name.to_string(), replacement: name.to_string() })
+                .collect();
+
+            return (name_start, candidates);
+        };
+
+        match name {
+            "m" | "angle" => {
+                let start = word_start(line, pos);
+                let candidates = ["rad", "deg", "grad"]
+                    .iter()
+                    .filter(|it| it.starts_with(&line[start..pos]))
+                    .map(|it| Pair { display: it.to_string(), replacement: it.to_string() })
+                    .collect();
+
+                (start, candidates)
+            }
+            _ => (pos, Vec::new()),
+        }
+    }
+}
+
+impl Completer for Helper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        if cmd::is_cmd(line.trim_start()) {
+            Ok(self.complete_cmd(line, pos))
+        } else {
+            Ok(self.complete_code(line, pos))
+        }
+    }
+}
+
+impl Hinter for Helper {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _: &Context<'_>) -> Option<String> {
+        // Hints are only displayed after the cursor, at the end of the line.
+        if (pos < line.len()) || cmd::is_cmd(line.trim_start()) {
+            return None;
+        }
+
+        // The operation of the innermost unclosed expression, which is the first word after the
+        // opening parenthesis or at the start of the line.
+        let mut depth = 0;
+        let expr_start = line
+            .char_indices()
+            .rev()
+            .find_map(|(idx, ch)| {
+                match ch {
+                    ')' => depth += 1,
+                    '(' if depth == 0 => return Some(idx + 1),
+                    '(' => depth -= 1,
+                    _ => {}
+                }
+
+                None
+            })
+            .unwrap_or(0);
+        let expr = &line[expr_start..];
+
+        // Only the operation has been entered so far.
+        let mut words = expr.split_whitespace();
+        let (Some(name), None) = (words.next(), words.next()) else {
+            return None;
+        };
+        let sig = self.sigs.get(name).filter(|it| !it.is_empty())?;
+
+        if expr.ends_with(char::is_whitespace) {
+            Some(sig.clone())
+        } else {
+            Some(format!(" {}", sig))
+        }
+    }
+}
+
+impl Highlighter for Helper {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(crate::color(
+            supports_color::Stream::Stdout,
+            hint.to_string(),
+            ansi_term::Style::new().dimmed(),
+        ))
+    }
+}
+
+impl Validator for Helper {}
+
+impl rustyline::Helper for Helper {}
+
+/// The index at which the word ending at `pos` begins.
+fn word_start(line: &str, pos: usize) -> usize {
+    line[..pos]
+        .char_indices()
+        .rev()
+        .find(|(_, ch)| ch.is_whitespace() || DELIMITERS.contains(ch))
+        .map_or(0, |(idx, ch)| idx + ch.len_utf8())
+}
@@ -5,14 +5,17 @@
 use imp_backend::span::Span;
 use super::Shell;
 
-pub fn process(this: &Shell, input: &str) {
-    let result = process_through_backend(input);
+pub fn process(this: &mut Shell, input: &str) {
+    let result = process_through_backend(this, input);
 
-    handle_backend_result(this, result);
+    handle_backend_result(this, input, result);
 }
 
-fn process_through_backend(input: &str) -> Result<Vec<imp_backend::evaluator::Output>, Span<imp_backend::Error>> {
-    imp_backend::process(
+fn process_through_backend(
+    this: &mut Shell,
+    input: &str,
+) -> Result<Vec<imp_backend::evaluator::Output>, Span<imp_backend::Error>> {
+    this.interp.process(
         input,
         imp_backend::Callbacks {
             inspect_lexer_output: Some(|out| {
@@ -26,7 +29,8 @@ fn process_through_backend(input: &str) -> Result<Vec<imp_backend::evaluator::Ou
 }
 
 fn handle_backend_result(
-    this: &Shell,
+    this: &mut Shell,
+    input: &str,
     result: Result<Vec<imp_backend::evaluator::Output>, Span<imp_backend::Error>>,
 ) {
     match result {
@@ -34,20 +38,20 @@ fn handle_backend_result(
             handle_backend_success(this, outputs);
         }
         Err(e) => {
-            handle_backend_error(this, e);
+            handle_backend_error(this, input, e);
         }
     }
 }
 
-fn handle_backend_success(this: &Shell, outputs: Vec<imp_backend::evaluator::Output>) {
+fn handle_backend_success(this: &mut Shell, outputs: Vec<imp_backend::evaluator::Output>) {
     for output in outputs {
         print_output(this, output);
     }
 }
 
-fn print_output(this: &Shell, output: imp_backend::evaluator::Output) {
+fn print_output(this: &mut Shell, output: imp_backend::evaluator::Output) {
     print_output_eq_sign(this);
-    print_output_value(output);
+    print_output_value(this, output);
     println!()
 }
 
@@ -62,13 +66,13 @@ fn print_output_eq_sign(this: &Shell) {
     );
 }
 
-fn print_output_value(output: imp_backend::evaluator::Output) {
+fn print_output_value(this: &mut Shell, output: imp_backend::evaluator::Output) {
     match output {
         imp_backend::evaluator::Output::Text(text) => {
             print_output_text(text.as_str());
         }
-        imp_backend::evaluator::Output::Graphic => {
-            print_output_graph(());
+        imp_backend::evaluator::Output::Graphic(graphic) => {
+            print_output_graph(this, &graphic);
         }
     }
 }
@@ -77,32 +81,76 @@ fn print_output_text(text: &str) {
     print!("{}", text);
 }
 
-fn print_output_graph(_: ()) {
-    todo!()
+/// Writes a graph to image files and prints their paths, draws the graph in the terminal, or both,
+/// depending on the configuration.
+fn print_output_graph(this: &mut Shell, graphic: &imp_backend::evaluator::graphic::Graphic) {
+    if this.config.plot.writes_files() {
+        this.plot_count += 1;
+        let stem = format!("imp-{}-{}", std::process::id(), this.plot_count);
+
+        match crate::plot::write(graphic, &this.config.plot, stem.as_str()) {
+            Ok((svg, png)) => {
+                print!("{}, {}", svg.display(), png.display());
+            }
+            Err(e) => {
+                eprint!("failed to write graph: {}", e);
+            }
+        }
+    }
+
+    if this.config.plot.terminal {
+        print!("\n{}", crate::plot::term::render(graphic, &this.config.plot));
+    }
 }
 
-fn handle_backend_error(this: &Shell, e: Span<imp_backend::Error>) {
-    use crate::err::{self, BackendStage};
-        use imp_backend::Error;
-
-        print_span(this, &e.range);
-
-        eprintln!(
-            "{}",
-            err::BackendError {
-                stage: match e.inner {
-                    Error::Lexer(_) => BackendStage::Lexer,
-                    Error::Parser(_) => BackendStage::Parser,
-                    Error::Evaluator(_) => BackendStage::Evaluator,
-                },
-                inner: e.inner,
+fn handle_backend_error(this: &Shell, input: &str, e: Span<imp_backend::Error>) {
+    use crate::err::{self, Stage};
+    use imp_backend::Error;
+
+    print_span(this, input, &e.range);
+
+    eprintln!(
+        "{}",
+        err::BackendError {
+            stage: match e.inner {
+                Error::Lexer(_) => Stage::Lexer,
+                Error::Parser(_) => Stage::Parser,
+                Error::Evaluator(_) => Stage::Evaluator,
             },
-        )
+            inner: e.inner.clone(),
+        },
+    );
+
+    if let Error::Evaluator(e) = e.inner {
+        print_backtrace(this, input, e.backtrace());
+    }
 }
 
-fn print_span(this: &Shell, range: &std::ops::Range<usize>) {
-    print_span_whitespace(this, range);
-    print_span_underline(this, range);
+/// Prints the expressions that enclosed the erroneous one, from innermost to outermost.
+fn print_backtrace(this: &Shell, input: &str, backtrace: &[Span<imp_backend::evaluator::err::Frame>]) {
+    for frame in backtrace {
+        print_span(this, input, &frame.range);
+        eprintln!("{}", crate::err::Note(&frame.inner));
+    }
+}
+
+fn print_span(this: &Shell, input: &str, range: &std::ops::Range<usize>) {
+    // Spans index characters within all lines of input, so they must be made relative to the line
+    // in which they begin.
+    let (line_idx, col) = crate::input::locate(input, range.start);
+    let line = input.split('\n').nth(line_idx).unwrap_or_default();
+    // Spans that continue onto later lines are cut off at the end of the first.
+    let end = (col + (range.end - range.start)).min(line.chars().count().max(col));
+    let range = col..end;
+
+    // The last line of input is directly above; earlier lines must be printed again.
+    if line_idx + 1 < input.split('\n').count() {
+        print_span_whitespace(this, &(0..0));
+        eprintln!("{}", line);
+    }
+
+    print_span_whitespace(this, &range);
+    print_span_underline(this, &range);
 }
 
 fn print_span_whitespace(this: &Shell, range: &std::ops::Range<usize>) {